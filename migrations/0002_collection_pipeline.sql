@@ -0,0 +1,229 @@
+-- Tudo o que a coleta passou a guardar depois do schema inicial. Cada instrução é idempotente: bancos
+-- criados antes do controle de versões podem já ter parte disto
+
+ALTER TABLE telegram_messages ADD COLUMN IF NOT EXISTS forward_from_message_id BIGINT;
+ALTER TABLE telegram_messages ADD COLUMN IF NOT EXISTS forward_sender_name VARCHAR(255);
+ALTER TABLE telegram_messages ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE telegram_messages ADD COLUMN IF NOT EXISTS deletion_source VARCHAR(50);
+ALTER TABLE telegram_messages ADD COLUMN IF NOT EXISTS language VARCHAR(10);
+ALTER TABLE telegram_messages ADD COLUMN IF NOT EXISTS search_config VARCHAR(32) NOT NULL DEFAULT 'simple';
+ALTER TABLE telegram_messages ADD COLUMN IF NOT EXISTS search_vector TSVECTOR;
+ALTER TABLE telegram_messages ADD COLUMN IF NOT EXISTS collector_account_id VARCHAR(64);
+
+-- Chats privados e grupos básicos numeram as mensagens por conta, então cada conta tem a sua
+-- linha (collector_account_id); canais e supergrupos são compartilhados (NULL)
+ALTER TABLE telegram_groups ADD COLUMN IF NOT EXISTS collector_account_id VARCHAR(64);
+ALTER TABLE telegram_groups DROP CONSTRAINT IF EXISTS telegram_groups_telegram_chat_id_key;
+ALTER TABLE telegram_groups DROP CONSTRAINT IF EXISTS telegram_groups_telegram_chat_id_collector_account_id_key;
+
+CREATE TABLE IF NOT EXISTS telegram_message_versions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    message_id UUID NOT NULL REFERENCES telegram_messages(id),
+    message_text TEXT,
+    edit_date TIMESTAMP WITH TIME ZONE NOT NULL,
+    entities JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(message_id, edit_date)
+);
+
+CREATE TABLE IF NOT EXISTS telegram_media (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    message_id UUID UNIQUE NOT NULL REFERENCES telegram_messages(id),
+    sha256 CHAR(64) NOT NULL,
+    sha1 CHAR(40) NOT NULL,
+    md5 CHAR(32) NOT NULL,
+    file_size BIGINT NOT NULL,
+    mime_type VARCHAR(100),
+    file_name VARCHAR(255),
+    storage_path TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS backfill_state (
+    group_id UUID PRIMARY KEY REFERENCES telegram_groups(id),
+    oldest_message_id BIGINT,
+    oldest_message_date TIMESTAMP WITH TIME ZONE,
+    messages_processed BIGINT NOT NULL DEFAULT 0,
+    completed BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS chat_sync_state (
+    group_id UUID PRIMARY KEY REFERENCES telegram_groups(id),
+    last_message_id BIGINT NOT NULL,
+    last_message_date TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS message_entities (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    message_id UUID NOT NULL REFERENCES telegram_messages(id),
+    entity_type VARCHAR(50) NOT NULL,
+    entity_offset INTEGER NOT NULL,
+    entity_length INTEGER NOT NULL,
+    url TEXT,
+    mentioned_user_id BIGINT,
+    language VARCHAR(100),
+    entity_text TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(message_id, entity_type, entity_offset, entity_length)
+);
+
+CREATE TABLE IF NOT EXISTS iocs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    ioc_type VARCHAR(50) NOT NULL,
+    value TEXT NOT NULL,
+    first_seen TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_seen TIMESTAMP WITH TIME ZONE NOT NULL,
+    sighting_count BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(ioc_type, value)
+);
+
+CREATE TABLE IF NOT EXISTS message_iocs (
+    message_id UUID NOT NULL REFERENCES telegram_messages(id),
+    ioc_id UUID NOT NULL REFERENCES iocs(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (message_id, ioc_id)
+);
+
+CREATE TABLE IF NOT EXISTS watchlists (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(255) NOT NULL,
+    pattern TEXT NOT NULL,
+    match_type VARCHAR(50) NOT NULL CHECK (match_type IN ('literal', 'case_insensitive', 'regex', 'fuzzy')),
+    max_distance INTEGER,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS alerts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    watchlist_id UUID NOT NULL REFERENCES watchlists(id),
+    message_id UUID NOT NULL REFERENCES telegram_messages(id),
+    group_id UUID REFERENCES telegram_groups(id),
+    match_start INTEGER NOT NULL,
+    match_end INTEGER NOT NULL,
+    matched_text TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(watchlist_id, message_id, match_start, match_end)
+);
+
+-- Política de captura por chat; prevalece sobre as regras do f1000.toml com o mesmo seletor
+CREATE TABLE IF NOT EXISTS chat_policies (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    selector_type VARCHAR(20) NOT NULL CHECK (selector_type IN ('id', 'username', 'chat_type')),
+    selector VARCHAR(255) NOT NULL,
+    mode VARCHAR(20) NOT NULL CHECK (mode IN ('ignore', 'metadata_only', 'text', 'text_media', 'full')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(selector_type, selector)
+);
+
+CREATE TABLE IF NOT EXISTS notification_deliveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    alert_id UUID NOT NULL REFERENCES alerts(id),
+    notifier VARCHAR(100) NOT NULL,
+    status VARCHAR(50) NOT NULL CHECK (status IN ('sent', 'failed', 'rate_limited')),
+    attempts INTEGER NOT NULL,
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Um chat por conta; o COALESCE trata o NULL dos chats compartilhados como um valor (NULLS NOT DISTINCT só existe a partir do Postgres 15)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_telegram_groups_chat_account ON telegram_groups(telegram_chat_id, COALESCE(collector_account_id, ''));
+
+-- A busca em português fixo deu lugar ao search_vector no idioma de cada mensagem
+DROP INDEX IF EXISTS idx_telegram_messages_text;
+CREATE INDEX IF NOT EXISTS idx_telegram_messages_forward_user ON telegram_messages(forward_from_user_id);
+CREATE INDEX IF NOT EXISTS idx_telegram_messages_forward_group ON telegram_messages(forward_from_group_id);
+CREATE INDEX IF NOT EXISTS idx_telegram_messages_search_vector ON telegram_messages USING gin(search_vector);
+CREATE INDEX IF NOT EXISTS idx_telegram_messages_language ON telegram_messages(language);
+CREATE INDEX IF NOT EXISTS idx_telegram_messages_deleted_at ON telegram_messages(deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_telegram_message_versions_message_id ON telegram_message_versions(message_id);
+
+CREATE INDEX IF NOT EXISTS idx_telegram_media_sha256 ON telegram_media(sha256);
+CREATE INDEX IF NOT EXISTS idx_telegram_media_sha1 ON telegram_media(sha1);
+CREATE INDEX IF NOT EXISTS idx_telegram_media_md5 ON telegram_media(md5);
+
+CREATE INDEX IF NOT EXISTS idx_message_entities_message_id ON message_entities(message_id);
+CREATE INDEX IF NOT EXISTS idx_message_entities_type ON message_entities(entity_type);
+CREATE INDEX IF NOT EXISTS idx_message_entities_mentioned_user ON message_entities(mentioned_user_id);
+
+CREATE INDEX IF NOT EXISTS idx_iocs_value ON iocs(lower(value));
+CREATE INDEX IF NOT EXISTS idx_iocs_last_seen ON iocs(last_seen);
+CREATE INDEX IF NOT EXISTS idx_message_iocs_ioc_id ON message_iocs(ioc_id);
+
+CREATE INDEX IF NOT EXISTS idx_alerts_message_id ON alerts(message_id);
+CREATE INDEX IF NOT EXISTS idx_alerts_group_id ON alerts(group_id);
+CREATE INDEX IF NOT EXISTS idx_alerts_watchlist_id ON alerts(watchlist_id);
+CREATE INDEX IF NOT EXISTS idx_alerts_created_at ON alerts(created_at);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_alert_id ON notification_deliveries(alert_id);
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_status ON notification_deliveries(status);
+
+DROP TRIGGER IF EXISTS update_backfill_state_updated_at ON backfill_state;
+CREATE TRIGGER update_backfill_state_updated_at 
+    BEFORE UPDATE ON backfill_state 
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_chat_sync_state_updated_at ON chat_sync_state;
+CREATE TRIGGER update_chat_sync_state_updated_at 
+    BEFORE UPDATE ON chat_sync_state 
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_iocs_updated_at ON iocs;
+CREATE TRIGGER update_iocs_updated_at 
+    BEFORE UPDATE ON iocs 
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_watchlists_updated_at ON watchlists;
+CREATE TRIGGER update_watchlists_updated_at 
+    BEFORE UPDATE ON watchlists 
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_chat_policies_updated_at ON chat_policies;
+CREATE TRIGGER update_chat_policies_updated_at 
+    BEFORE UPDATE ON chat_policies 
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- search_vector usa a configuração do idioma detectado em cada mensagem (simple quando não há uma)
+CREATE OR REPLACE FUNCTION update_message_search_vector()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector = to_tsvector(NEW.search_config::regconfig, coalesce(NEW.message_text, ''));
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS update_telegram_messages_search_vector ON telegram_messages;
+CREATE TRIGGER update_telegram_messages_search_vector
+    BEFORE INSERT OR UPDATE OF message_text, search_config ON telegram_messages
+    FOR EACH ROW EXECUTE FUNCTION update_message_search_vector();
+
+-- Mensagens gravadas antes do trigger ficam pesquisáveis com a configuração simple
+UPDATE telegram_messages SET search_config = search_config WHERE search_vector IS NULL;
+
+-- Consulta em todas as configurações de uma vez: cada mensagem casa com os lexemas do próprio idioma
+-- (serbian translitera cirílico, então termos russos também encontram o equivalente latino)
+CREATE OR REPLACE FUNCTION multilingual_tsquery(query TEXT)
+RETURNS tsquery AS $$
+DECLARE
+    config TEXT;
+    combined tsquery := websearch_to_tsquery('simple', query);
+BEGIN
+    FOREACH config IN ARRAY ARRAY[
+        'arabic', 'danish', 'dutch', 'english', 'finnish', 'french', 'german', 'greek', 'hungarian',
+        'indonesian', 'italian', 'lithuanian', 'nepali', 'norwegian', 'portuguese', 'romanian',
+        'russian', 'serbian', 'spanish', 'swedish', 'tamil', 'turkish'
+    ] LOOP
+        combined := combined || websearch_to_tsquery(config::regconfig, query);
+    END LOOP;
+    RETURN combined;
+END;
+$$ language 'plpgsql' STABLE;
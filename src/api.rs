@@ -0,0 +1,245 @@
+use crate::config::{parse_date, ApiConfig};
+use crate::db::{Cursor, Database, Ioc, MessageSearch, TelegramGroup, TelegramMessage, TelegramUser};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Clone)]
+struct ApiState {
+    database: Database,
+    config: Arc<ApiConfig>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PageParams {
+    pub cursor: Option<String>,
+    pub limit: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MessageParams {
+    pub group_id: Option<String>,
+    pub user_id: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub message_type: Option<String>,
+    pub has_media: Option<String>,
+    pub q: Option<String>,
+    pub ioc: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IocParams {
+    pub value: String,
+}
+
+pub fn router(database: Database, config: ApiConfig) -> Router {
+    let state = ApiState { database, config: Arc::new(config) };
+
+    Router::new()
+        .route("/api/groups", get(list_groups))
+        .route("/api/groups/:id", get(get_group))
+        .route("/api/users", get(list_users))
+        .route("/api/users/:id", get(get_user))
+        .route("/api/messages", get(search_messages))
+        .route("/api/messages/:id", get(get_message))
+        .route("/api/iocs", get(find_iocs))
+        .layer(middleware::from_fn_with_state(state.clone(), require_api_key))
+        .with_state(state)
+}
+
+pub async fn serve(database: Database, config: ApiConfig) -> Result<(), Box<dyn std::error::Error>> {
+    if config.api_keys.is_empty() {
+        return Err("a API exige ao menos uma chave em API_KEYS".into());
+    }
+
+    let listener = tokio::net::TcpListener::bind(&config.bind).await?;
+    info!("🌐 API REST ouvindo em http://{}/api", listener.local_addr()?);
+
+    axum::serve(listener, router(database, config)).await?;
+    Ok(())
+}
+
+// Authorization: Bearer <chave> ou X-API-Key
+pub fn is_authorized(config: &ApiConfig, headers: &HeaderMap) -> bool {
+    let key = headers.get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| headers.get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer ")));
+
+    key.is_some_and(|key| config.api_keys.iter().any(|configured| configured == key.trim()))
+}
+
+async fn require_api_key(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    if is_authorized(&state.config, request.headers()) {
+        return next.run(request).await;
+    }
+    ApiError::new(StatusCode::UNAUTHORIZED, "chave de API ausente ou inválida").into_response()
+}
+
+async fn list_groups(State(state): State<ApiState>, Query(params): Query<PageParams>) -> Result<Json<Page<TelegramGroup>>, ApiError> {
+    let (after, limit) = page_params(&params)?;
+    let groups = TelegramGroup::find_page(state.database.get_pool(), after, limit + 1).await?;
+    Ok(Json(page(groups, limit, |group| Cursor::new(group.created_at, group.id))))
+}
+
+async fn get_group(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<TelegramGroup>, ApiError> {
+    let id = parse_uuid("id", &id)?;
+    let group = TelegramGroup::find_by_ids(state.database.get_pool(), &[id]).await?.into_iter().next();
+    group.map(Json).ok_or_else(|| ApiError::not_found(format!("grupo não encontrado: {}", id)))
+}
+
+async fn list_users(State(state): State<ApiState>, Query(params): Query<PageParams>) -> Result<Json<Page<TelegramUser>>, ApiError> {
+    let (after, limit) = page_params(&params)?;
+    let users = TelegramUser::find_page(state.database.get_pool(), after, limit + 1).await?;
+    Ok(Json(page(users, limit, |user| Cursor::new(user.created_at, user.id))))
+}
+
+async fn get_user(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<TelegramUser>, ApiError> {
+    let id = parse_uuid("id", &id)?;
+    let user = TelegramUser::find_by_ids(state.database.get_pool(), &[id]).await?.into_iter().next();
+    user.map(Json).ok_or_else(|| ApiError::not_found(format!("usuário não encontrado: {}", id)))
+}
+
+async fn search_messages(State(state): State<ApiState>, Query(params): Query<MessageParams>) -> Result<Json<Page<TelegramMessage>>, ApiError> {
+    let mut search = message_search(&params)?;
+    let limit = search.limit;
+    search.limit += 1;
+    let messages = TelegramMessage::search(state.database.get_pool(), &search).await?;
+    Ok(Json(page(messages, limit, |message| Cursor::new(message.date, message.id))))
+}
+
+async fn get_message(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<TelegramMessage>, ApiError> {
+    let id = parse_uuid("id", &id)?;
+    let message = TelegramMessage::find_by_id(state.database.get_pool(), id).await?;
+    message.map(Json).ok_or_else(|| ApiError::not_found(format!("mensagem não encontrada: {}", id)))
+}
+
+// As mensagens que citam o IOC saem de /api/messages?ioc=<valor>
+async fn find_iocs(State(state): State<ApiState>, Query(params): Query<IocParams>) -> Result<Json<Page<Ioc>>, ApiError> {
+    let iocs = Ioc::find_by_value(state.database.get_pool(), params.value.trim()).await?;
+    Ok(Json(Page { data: iocs, next_cursor: None }))
+}
+
+// Os handlers buscam limit + 1 linhas: a sobra indica que existe próxima página
+pub fn page<T>(mut rows: Vec<T>, limit: i64, cursor: impl Fn(&T) -> Cursor) -> Page<T> {
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+
+    let next_cursor = if has_more { rows.last().map(|row| cursor(row).encode()) } else { None };
+    Page { data: rows, next_cursor }
+}
+
+pub fn page_params(params: &PageParams) -> Result<(Option<Cursor>, i64), ApiError> {
+    Ok((parse_cursor(params.cursor.as_deref())?, parse_limit(params.limit.as_deref())?))
+}
+
+pub fn message_search(params: &MessageParams) -> Result<MessageSearch, ApiError> {
+    let non_empty = |value: &Option<String>| value.as_deref().map(str::trim).filter(|value| !value.is_empty()).map(str::to_string);
+
+    Ok(MessageSearch {
+        group_id: params.group_id.as_deref().map(|id| parse_uuid("group_id", id)).transpose()?,
+        user_id: params.user_id.as_deref().map(|id| parse_uuid("user_id", id)).transpose()?,
+        since: params.since.as_deref().map(|date| parse_param_date("since", date)).transpose()?,
+        // Uma data sem horário em until inclui o dia inteiro
+        until: params.until.as_deref()
+            .map(|date| parse_param_date("until", date)
+                .map(|until| if date.len() == 10 { until + chrono::Duration::days(1) } else { until }))
+            .transpose()?,
+        message_type: non_empty(&params.message_type),
+        has_media: params.has_media.as_deref()
+            .map(|value| match value {
+                "true" | "1" => Ok(true),
+                "false" | "0" => Ok(false),
+                _ => Err(ApiError::bad_request(format!("has_media deve ser true ou false: {}", value))),
+            })
+            .transpose()?,
+        text: non_empty(&params.q),
+        ioc: non_empty(&params.ioc),
+        before: parse_cursor(params.cursor.as_deref())?,
+        limit: parse_limit(params.limit.as_deref())?,
+    })
+}
+
+fn parse_cursor(cursor: Option<&str>) -> Result<Option<Cursor>, ApiError> {
+    cursor
+        .map(|cursor| Cursor::decode(cursor).ok_or_else(|| ApiError::bad_request(format!("cursor inválido: {}", cursor))))
+        .transpose()
+}
+
+fn parse_limit(limit: Option<&str>) -> Result<i64, ApiError> {
+    match limit {
+        Some(limit) => limit.parse::<i64>()
+            .map(|limit| limit.clamp(1, MAX_PAGE_SIZE))
+            .map_err(|_| ApiError::bad_request(format!("limit inválido: {}", limit))),
+        None => Ok(DEFAULT_PAGE_SIZE),
+    }
+}
+
+fn parse_uuid(name: &str, value: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(value).map_err(|_| ApiError::bad_request(format!("{} deve ser um UUID: {}", name, value)))
+}
+
+fn parse_param_date(name: &str, value: &str) -> Result<chrono::DateTime<chrono::Utc>, ApiError> {
+    parse_date(value).ok_or_else(|| ApiError::bad_request(format!("{} deve estar no formato AAAA-MM-DD ou RFC 3339: {}", name, value)))
+}
+
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: &str) -> Self {
+        Self { status, message: message.to_string() }
+    }
+
+    fn bad_request(message: String) -> Self {
+        Self { status: StatusCode::BAD_REQUEST, message }
+    }
+
+    fn not_found(message: String) -> Self {
+        Self { status: StatusCode::NOT_FOUND, message }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        warn!("❌ Erro no banco ao atender requisição da API: {}", e);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "falha ao consultar o banco de dados")
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests;
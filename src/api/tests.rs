@@ -0,0 +1,157 @@
+use super::*;
+use crate::db::test_support::{create_group, minimal_message, random_telegram_id, test_database};
+use crate::db::{NewTelegramMessage, NewTelegramUser};
+use crate::test_support::serve;
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+
+fn config() -> ApiConfig {
+    ApiConfig {
+        bind: "127.0.0.1:0".to_string(),
+        api_keys: vec!["chave".to_string()],
+    }
+}
+
+#[test]
+fn accepts_bearer_and_header_keys() {
+    let config = config();
+    let mut headers = HeaderMap::new();
+    assert!(!is_authorized(&config, &headers));
+
+    headers.insert(header::AUTHORIZATION, "Bearer chave".parse().unwrap());
+    assert!(is_authorized(&config, &headers));
+
+    headers.insert("x-api-key", "outra".parse().unwrap());
+    assert!(!is_authorized(&config, &headers));
+}
+
+#[test]
+fn parses_message_filters() {
+    let group_id = Uuid::new_v4();
+    let search = message_search(&MessageParams {
+        group_id: Some(group_id.to_string()),
+        since: Some("2024-05-01".to_string()),
+        until: Some("2024-05-31".to_string()),
+        has_media: Some("true".to_string()),
+        q: Some("  ".to_string()),
+        ioc: Some("44d88612fea8a8f36de82e1278abb02f".to_string()),
+        limit: Some("10000".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(search.group_id, Some(group_id));
+    assert_eq!(search.since, Some(Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap()));
+    assert_eq!(search.until, Some(Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap()));
+    assert_eq!(search.has_media, Some(true));
+    assert_eq!(search.text, None);
+    assert_eq!(search.ioc.as_deref(), Some("44d88612fea8a8f36de82e1278abb02f"));
+    assert_eq!(search.limit, MAX_PAGE_SIZE);
+}
+
+#[test]
+fn rejects_invalid_filters() {
+    let invalid = [
+        MessageParams { group_id: Some("grupo".to_string()), ..Default::default() },
+        MessageParams { since: Some("ontem".to_string()), ..Default::default() },
+        MessageParams { has_media: Some("talvez".to_string()), ..Default::default() },
+        MessageParams { cursor: Some("???".to_string()), ..Default::default() },
+        MessageParams { limit: Some("muitos".to_string()), ..Default::default() },
+    ];
+
+    for params in invalid {
+        assert_eq!(message_search(&params).unwrap_err().status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[test]
+fn page_emits_cursor_only_when_rows_remain() {
+    let date = Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap();
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+
+    let full = page(ids.clone(), 2, |id| Cursor::new(date, *id));
+    assert_eq!(full.data, ids[..2]);
+    assert_eq!(full.next_cursor, Some(Cursor::new(date, ids[1]).encode()));
+
+    let last = page(ids[2..].to_vec(), 2, |id| Cursor::new(date, *id));
+    assert_eq!(last.next_cursor, None);
+}
+
+async fn spawn_server(database: Database) -> String {
+    format!("http://{}/api", serve(router(database, config())).await)
+}
+
+async fn get(url: &str) -> (u16, Value) {
+    let response = reqwest::Client::new().get(url).bearer_auth("chave").send().await.unwrap();
+    (response.status().as_u16(), response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn searches_messages_by_ioc_with_cursor_pagination() {
+    let database = test_database().await;
+    let pool = database.get_pool();
+
+    let group = create_group(pool, "supergroup").await;
+    let user = TelegramUser::create(pool, NewTelegramUser {
+        telegram_user_id: random_telegram_id(),
+        username: Some("api_user".to_string()),
+        first_name: None,
+        last_name: None,
+        phone_number: None,
+        is_bot: false,
+        is_verified: false,
+        is_premium: false,
+        language_code: None,
+    })
+    .await
+    .unwrap();
+
+    let hash = Uuid::new_v4().simple().to_string();
+    let mut message_ids = Vec::new();
+    for (telegram_message_id, day) in [(1, 1), (2, 2), (3, 3)] {
+        let message = TelegramMessage::create(pool, NewTelegramMessage {
+            telegram_message_id,
+            user_id: Some(user.id),
+            message_text: Some(format!("amostra {}", hash.to_uppercase())),
+            message_type: "text".to_string(),
+            date: Utc.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap(),
+            ..minimal_message(group.id)
+        })
+        .await
+        .unwrap();
+        Ioc::record_sighting(pool, message.id, "md5", &hash, message.date).await.unwrap();
+        message_ids.push(message.id);
+    }
+    let base = spawn_server(database).await;
+
+    let unauthorized = reqwest::get(format!("{}/groups", base)).await.unwrap();
+    assert_eq!(unauthorized.status().as_u16(), 401);
+
+    let (status, found) = get(&format!("{}/iocs?value={}", base, hash.to_uppercase())).await;
+    assert_eq!(status, 200);
+    assert_eq!(found["data"][0]["sighting_count"], 3);
+
+    let (_, first) = get(&format!("{}/messages?ioc={}&limit=2", base, hash)).await;
+    let first_ids: Vec<&str> = first["data"].as_array().unwrap().iter().map(|message| message["id"].as_str().unwrap()).collect();
+    assert_eq!(first_ids, [message_ids[2].to_string(), message_ids[1].to_string()]);
+
+    let (_, second) = get(&format!("{}/messages?ioc={}&limit=2&cursor={}", base, hash, first["next_cursor"].as_str().unwrap())).await;
+    assert_eq!(second["data"].as_array().unwrap().len(), 1);
+    assert_eq!(second["data"][0]["id"], message_ids[0].to_string());
+    assert!(second["next_cursor"].is_null());
+
+    let (_, filtered) = get(&format!("{}/messages?group_id={}&user_id={}&since=2024-05-02&until=2024-05-02&q={}", base, group.id, user.id, hash)).await;
+    assert_eq!(filtered["data"].as_array().unwrap().len(), 1);
+    assert_eq!(filtered["data"][0]["id"], message_ids[1].to_string());
+
+    let (_, with_media) = get(&format!("{}/messages?group_id={}&has_media=true", base, group.id)).await;
+    assert!(with_media["data"].as_array().unwrap().is_empty());
+
+    let (status, fetched) = get(&format!("{}/groups/{}", base, group.id)).await;
+    assert_eq!(status, 200);
+    assert_eq!(fetched["telegram_chat_id"], group.telegram_chat_id);
+
+    let (status, error) = get(&format!("{}/users/{}", base, Uuid::new_v4())).await;
+    assert_eq!(status, 404);
+    assert!(error["error"].as_str().unwrap().contains("usuário"));
+}
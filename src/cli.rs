@@ -0,0 +1,421 @@
+use crate::export::ExportOptions;
+use crate::telegram::{LoginError, SessionRevoked};
+use std::path::PathBuf;
+use tracing::Level;
+
+pub const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+pub const USAGE: &str = "\
+F1000 - coleta de threat intel no Telegram
+
+Uso: f1000 [opções globais] <comando> [argumentos]
+
+Comandos:
+  listen              coleta mensagens em tempo real (padrão sem comando)
+  login               autentica a conta e salva a sessão
+  backfill            coleta o histórico dos chats da conta
+  dialogs             lista os chats da conta
+  join <chat>         entra num grupo ou canal (@username, t.me/... ou convite)
+  export <formato>    exporta a coleta em STIX 2.1 ou MISP
+  search <termos>     busca textual nas mensagens coletadas
+  stats               resumo do que já foi coletado
+  migrate             aplica o schema no banco de dados
+  session info        mostra o estado do arquivo de sessão
+  config check        valida a configuração e mostra os valores efetivos
+  taxii               servidor TAXII 2.1 com os indicadores
+  api                 API REST de consulta
+  help [comando]      mostra esta ajuda ou a de um comando
+
+Opções globais:
+  --config <arquivo>  f1000.toml ou .env (padrão: f1000.toml e .env no diretório atual)
+  --log-level <nível> error, warn, info, debug ou trace (padrão: info)
+  --account <id>      conta de TELEGRAM_ACCOUNTS usada (padrão: login, dialogs, join e session
+                      usam a primeira; listen e backfill usam todas)
+  -h, --help          mostra a ajuda
+  -V, --version       mostra a versão
+
+Códigos de saída:
+  0   sucesso
+  1   falha durante a execução
+  64  uso incorreto da linha de comando
+  69  banco de dados ou Telegram indisponível
+  77  sessão do Telegram ausente ou revogada
+  78  configuração inválida
+";
+
+const LISTEN_HELP: &str = "\
+Uso: f1000 listen
+
+Recupera as mensagens perdidas desde a última execução e passa a coletar em tempo real. Chats
+com política full têm o histórico coletado em segundo plano, como no backfill.
+SIGINT/SIGTERM encerram a coleta concluindo o trabalho pendente. Exige sessão autorizada;
+sem ela o login é feito na hora pelo provedor de TELEGRAM_CREDENTIALS.
+";
+
+const LOGIN_HELP: &str = "\
+Uso: f1000 login
+
+Pede o código de verificação (e a senha 2FA, se houver) e salva a sessão em TELEGRAM_SESSION_PATH.
+TELEGRAM_LOGIN_METHOD=qr mostra um QR code para escanear no app em vez de enviar código.
+TELEGRAM_CREDENTIALS escolhe de onde vêm código e senha: stdin, env (TELEGRAM_LOGIN_CODE,
+TELEGRAM_2FA_PASSWORD), file (TELEGRAM_CREDENTIALS_FILE, arquivo ou named pipe) ou http
+(formulário temporário em TELEGRAM_CREDENTIALS_BIND). Código ou senha errados são pedidos de
+novo até TELEGRAM_LOGIN_ATTEMPTS vezes.
+";
+
+const BACKFILL_HELP: &str = "\
+Uso: f1000 backfill
+
+Percorre o histórico de todos os chats da conta que a política não ignora, do mais novo para o
+mais antigo, retomando do último cursor salvo. Limites em BACKFILL_UNTIL_DATE e BACKFILL_UNTIL_MESSAGE_ID.
+";
+
+const DIALOGS_HELP: &str = "\
+Uso: f1000 dialogs
+
+Lista os chats da conta em stdout, um por linha: id, tipo, @username e nome, separados por tab.
+";
+
+const JOIN_HELP: &str = "\
+Uso: f1000 join <chat>
+
+Entra num grupo ou canal. <chat> pode ser @username, https://t.me/username,
+https://t.me/+HASH ou https://t.me/joinchat/HASH.
+";
+
+const EXPORT_HELP: &str = "\
+Uso: f1000 export <stix|misp> [opções]
+
+Opções:
+  --group <id|@username>  apenas um grupo (UUID, id do Telegram ou @username)
+  --since <data>          mensagens a partir da data (AAAA-MM-DD ou RFC 3339)
+  --until <data>          mensagens até a data, inclusive
+  -o, --output <arquivo>  grava no arquivo em vez de stdout
+  --per-day               (misp) um evento por grupo e dia
+  --push                  (misp) envia os eventos para MISP_URL
+";
+
+const SEARCH_HELP: &str = "\
+Uso: f1000 search <termos> [opções]
+
+Busca textual em todos os idiomas, ordenada por relevância. Aceita a sintaxe de
+websearch do Postgres: \"frase exata\", OR e -exclusão.
+
+Opções:
+  --group <id|@username>  apenas um grupo (UUID, id do Telegram ou @username)
+  --language <código>     apenas mensagens no idioma (ISO 639-1, ex.: pt, ru)
+  --limit <n>             máximo de resultados (padrão: 20)
+";
+
+const STATS_HELP: &str = "\
+Uso: f1000 stats
+
+Mostra quantos grupos, usuários, mensagens, mídias, IOCs e alertas já foram coletados.
+";
+
+const MIGRATE_HELP: &str = "\
+Uso: f1000 migrate
+
+Aplica, em ordem, as migrações de migrations/ que o banco ainda não tem. Bancos criados
+antes do controle de versões são atualizados a partir do schema inicial.
+";
+
+const SESSION_HELP: &str = "\
+Uso: f1000 session info
+
+Mostra o caminho, o tamanho e o estado do arquivo de sessão sem conectar ao Telegram.
+Sai com código 77 se a sessão não estiver autorizada.
+";
+
+const CONFIG_HELP: &str = "\
+Uso: f1000 config check
+
+Lê o f1000.toml (ou o arquivo de --config) e as variáveis de ambiente, que têm prioridade, e
+mostra a configuração efetiva com segredos mascarados. Não conecta ao banco nem ao Telegram.
+Sai com código 78 listando cada campo inválido.
+";
+
+const TAXII_HELP: &str = "\
+Uso: f1000 taxii
+
+Servidor TAXII 2.1 em TAXII_BIND. Exige TAXII_USERNAME/TAXII_PASSWORD ou TAXII_API_KEYS.
+";
+
+const API_HELP: &str = "\
+Uso: f1000 api
+
+API REST em API_BIND. Exige ao menos uma chave em API_KEYS.
+";
+
+// Códigos de saída de sysexits.h, conhecidos por systemd e scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Success,
+    Failure,
+    Usage,
+    Unavailable,
+    NoPermission,
+    Config,
+}
+
+impl Exit {
+    pub fn code(self) -> u8 {
+        match self {
+            Exit::Success => 0,
+            Exit::Failure => 1,
+            Exit::Usage => 64,
+            Exit::Unavailable => 69,
+            Exit::NoPermission => 77,
+            Exit::Config => 78,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CliError {
+    pub exit: Exit,
+    pub message: String,
+}
+
+impl CliError {
+    pub fn new(exit: Exit, message: impl Into<String>) -> Self {
+        Self { exit, message: message.into() }
+    }
+
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self::new(Exit::Usage, message)
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+pub fn exit_for(error: &(dyn std::error::Error + 'static)) -> Exit {
+    if let Some(error) = error.downcast_ref::<CliError>() {
+        return error.exit;
+    }
+    if error.is::<SessionRevoked>() {
+        return Exit::NoPermission;
+    }
+    match error.downcast_ref::<LoginError>() {
+        Some(LoginError::Telegram(_)) => Exit::Unavailable,
+        Some(_) => Exit::NoPermission,
+        None => Exit::Failure,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JoinTarget {
+    Username(String),
+    InviteHash(String),
+}
+
+impl JoinTarget {
+    pub fn parse(target: &str) -> Option<Self> {
+        let target = target.trim();
+        let path = ["https://", "http://"].iter()
+            .find_map(|scheme| target.strip_prefix(scheme))
+            .unwrap_or(target);
+        let path = ["t.me/", "telegram.me/"].iter()
+            .find_map(|host| path.strip_prefix(host))
+            .map(|path| path.trim_end_matches('/'));
+
+        let (value, invite) = match path {
+            Some(path) => match path.strip_prefix('+').or_else(|| path.strip_prefix("joinchat/")) {
+                Some(hash) => (hash, true),
+                None => (path, false),
+            },
+            None => (target.strip_prefix('@').unwrap_or(target), false),
+        };
+
+        let valid = !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        match (valid, invite) {
+            (false, _) => None,
+            (true, true) => Some(JoinTarget::InviteHash(value.to_string())),
+            // Usernames não têm hífen
+            (true, false) if value.contains('-') => None,
+            (true, false) => Some(JoinTarget::Username(value.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchOptions {
+    pub query: String,
+    pub group: Option<String>,
+    pub language: Option<String>,
+    pub limit: i64,
+}
+
+impl SearchOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut args = args.iter();
+        let mut terms = Vec::new();
+        let mut options = SearchOptions { query: String::new(), group: None, language: None, limit: DEFAULT_SEARCH_LIMIT };
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                terms.push(arg.as_str());
+                continue;
+            }
+
+            let value = args.next().ok_or_else(|| format!("{} precisa de um valor", arg))?;
+            match arg.as_str() {
+                "--group" => options.group = Some(value.clone()),
+                "--language" => options.language = Some(value.to_lowercase()),
+                "--limit" => options.limit = value.parse::<i64>()
+                    .ok()
+                    .filter(|limit| *limit > 0)
+                    .ok_or_else(|| format!("--limit deve ser um número positivo: {}", value))?,
+                _ => return Err(format!("opção desconhecida: {}", arg)),
+            }
+        }
+
+        options.query = terms.join(" ");
+        if options.query.trim().is_empty() {
+            return Err("informe os termos da busca".to_string());
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Listen,
+    Login,
+    Backfill,
+    Dialogs,
+    Join(JoinTarget),
+    Export(ExportOptions),
+    Search(SearchOptions),
+    Stats,
+    Migrate,
+    SessionInfo,
+    ConfigCheck,
+    Taxii,
+    Api,
+    Help(Option<String>),
+    Version,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub config_path: Option<PathBuf>,
+    pub log_level: Level,
+    pub account: Option<String>,
+    pub command: Command,
+}
+
+impl Cli {
+    // Recebe os argumentos sem o nome do programa; opções globais valem em qualquer posição
+    pub fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut config_path = None;
+        let mut log_level = Level::INFO;
+        let mut account = None;
+        let mut help = false;
+        let mut rest: Vec<String> = Vec::new();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = |name: &str| inline.clone()
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| CliError::usage(format!("{} precisa de um valor", name)));
+
+            match flag {
+                "--config" => config_path = Some(PathBuf::from(value("--config")?)),
+                "--log-level" => {
+                    let level = value("--log-level")?;
+                    log_level = level.parse()
+                        .map_err(|_| CliError::usage(format!("nível de log inválido: {} (use error, warn, info, debug ou trace)", level)))?;
+                },
+                "--account" => account = Some(value("--account")?),
+                "-h" | "--help" => help = true,
+                "-V" | "--version" => return Ok(Cli { config_path, log_level, account, command: Command::Version }),
+                _ => rest.push(arg.clone()),
+            }
+        }
+
+        let command = match rest.first().map(String::as_str) {
+            None if help => Command::Help(None),
+            None => Command::Listen,
+            Some("help") => Command::Help(rest.get(1).cloned()),
+            Some("version") => Command::Version,
+            Some(name) if help => {
+                command_help(name).ok_or_else(|| CliError::usage(format!("comando desconhecido: {}", name)))?;
+                Command::Help(Some(name.to_string()))
+            },
+            Some(name) => parse_command(name, &rest[1..])?,
+        };
+
+        Ok(Cli { config_path, log_level, account, command })
+    }
+}
+
+fn parse_command(name: &str, args: &[String]) -> Result<Command, CliError> {
+    let no_args = |command: Command| match args.first() {
+        Some(extra) => Err(CliError::usage(format!("argumento inesperado para {}: {}", name, extra))),
+        None => Ok(command),
+    };
+
+    match name {
+        "listen" => no_args(Command::Listen),
+        "login" => no_args(Command::Login),
+        "backfill" => no_args(Command::Backfill),
+        "dialogs" => no_args(Command::Dialogs),
+        "stats" => no_args(Command::Stats),
+        "migrate" => no_args(Command::Migrate),
+        "taxii" => no_args(Command::Taxii),
+        "api" => no_args(Command::Api),
+        "join" => match args {
+            [target] => JoinTarget::parse(target)
+                .map(Command::Join)
+                .ok_or_else(|| CliError::usage(format!("chat inválido: {}", target))),
+            [] => Err(CliError::usage("informe o chat: f1000 join <@username|link>")),
+            [_, extra, ..] => Err(CliError::usage(format!("argumento inesperado para join: {}", extra))),
+        },
+        "export" => ExportOptions::from_args(args).map(Command::Export).map_err(CliError::usage),
+        "search" => SearchOptions::from_args(args).map(Command::Search).map_err(CliError::usage),
+        "session" => match args {
+            [action] if action == "info" => Ok(Command::SessionInfo),
+            [action, ..] => Err(CliError::usage(format!("ação desconhecida para session: {}", action))),
+            [] => Err(CliError::usage("informe a ação: f1000 session info")),
+        },
+        "config" => match args {
+            [action] if action == "check" => Ok(Command::ConfigCheck),
+            [action, ..] => Err(CliError::usage(format!("ação desconhecida para config: {}", action))),
+            [] => Err(CliError::usage("informe a ação: f1000 config check")),
+        },
+        _ => Err(CliError::usage(format!("comando desconhecido: {}", name))),
+    }
+}
+
+pub fn command_help(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "listen" => LISTEN_HELP,
+        "login" => LOGIN_HELP,
+        "backfill" => BACKFILL_HELP,
+        "dialogs" => DIALOGS_HELP,
+        "join" => JOIN_HELP,
+        "export" => EXPORT_HELP,
+        "search" => SEARCH_HELP,
+        "stats" => STATS_HELP,
+        "migrate" => MIGRATE_HELP,
+        "session" => SESSION_HELP,
+        "config" => CONFIG_HELP,
+        "taxii" => TAXII_HELP,
+        "api" => API_HELP,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests;
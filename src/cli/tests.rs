@@ -0,0 +1,106 @@
+use super::*;
+use crate::export::ExportFormat;
+
+fn parse(args: &[&str]) -> Result<Cli, CliError> {
+    Cli::parse(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+}
+
+#[test]
+fn defaults_to_listen_with_info_logs() {
+    let cli = parse(&[]).unwrap();
+    assert_eq!(cli.command, Command::Listen);
+    assert_eq!(cli.log_level, Level::INFO);
+    assert_eq!(cli.config_path, None);
+}
+
+#[test]
+fn global_flags_are_accepted_anywhere() {
+    let cli = parse(&["stats", "--log-level", "debug", "--config=/etc/f1000/f1000.env"]).unwrap();
+    assert_eq!(cli.command, Command::Stats);
+    assert_eq!(cli.log_level, Level::DEBUG);
+    assert_eq!(cli.config_path, Some(PathBuf::from("/etc/f1000/f1000.env")));
+
+    let cli = parse(&["--config", "prod.env", "session", "info"]).unwrap();
+    assert_eq!(cli.command, Command::SessionInfo);
+    assert_eq!(cli.config_path, Some(PathBuf::from("prod.env")));
+
+    let cli = parse(&["login", "--account=burner1"]).unwrap();
+    assert_eq!(cli.command, Command::Login);
+    assert_eq!(cli.account.as_deref(), Some("burner1"));
+}
+
+#[test]
+fn help_flag_shows_the_command_help() {
+    assert_eq!(parse(&["--help"]).unwrap().command, Command::Help(None));
+    assert_eq!(parse(&["search", "-h"]).unwrap().command, Command::Help(Some("search".to_string())));
+    assert_eq!(parse(&["help", "export"]).unwrap().command, Command::Help(Some("export".to_string())));
+    assert_eq!(parse(&["-V"]).unwrap().command, Command::Version);
+
+    for name in ["listen", "login", "backfill", "dialogs", "join", "export", "search", "stats", "migrate", "session", "config", "taxii", "api"] {
+        assert!(command_help(name).is_some_and(|help| help.starts_with("Uso: f1000")), "{}", name);
+    }
+}
+
+#[test]
+fn parses_command_arguments() {
+    let Command::Export(options) = parse(&["export", "misp", "--push", "--since", "2024-05-01"]).unwrap().command else {
+        panic!("esperava export");
+    };
+    assert_eq!(options.format, ExportFormat::Misp);
+    assert!(options.push);
+
+    let Command::Search(options) = parse(&["search", "stealer", "logs", "--language", "RU", "--limit", "5"]).unwrap().command else {
+        panic!("esperava search");
+    };
+    assert_eq!(options.query, "stealer logs");
+    assert_eq!(options.language.as_deref(), Some("ru"));
+    assert_eq!(options.limit, 5);
+
+    assert_eq!(parse(&["config", "check"]).unwrap().command, Command::ConfigCheck);
+    assert_eq!(parse(&["join", "@canal_exemplo"]).unwrap().command, Command::Join(JoinTarget::Username("canal_exemplo".to_string())));
+}
+
+#[test]
+fn usage_errors_exit_with_64() {
+    for args in [
+        &["deploy"][..],
+        &["stats", "extra"],
+        &["search"],
+        &["search", "x", "--limit", "0"],
+        &["session"],
+        &["session", "delete"],
+        &["config"],
+        &["join"],
+        &["join", "não é chat"],
+        &["export", "csv"],
+        &["--log-level", "verbose"],
+        &["--config"],
+    ] {
+        let error = parse(args).unwrap_err();
+        assert_eq!(error.exit, Exit::Usage, "{:?}", args);
+        assert_eq!(exit_for(&error), Exit::Usage);
+    }
+    assert_eq!(Exit::Usage.code(), 64);
+}
+
+#[test]
+fn revoked_sessions_map_to_no_permission() {
+    let revoked = SessionRevoked { reason: "AUTH_KEY_UNREGISTERED".to_string() };
+    assert_eq!(exit_for(&revoked), Exit::NoPermission);
+    assert_eq!(exit_for(&std::io::Error::other("falha")), Exit::Failure);
+    assert_eq!(exit_for(&CliError::new(Exit::Config, "TELEGRAM_API_ID inválido")).code(), 78);
+    assert_eq!(exit_for(&LoginError::InvalidPassword), Exit::NoPermission);
+    assert_eq!(exit_for(&LoginError::Unavailable("TELEGRAM_LOGIN_CODE não definido".to_string())), Exit::NoPermission);
+}
+
+#[test]
+fn join_target_accepts_usernames_and_invite_links() {
+    assert_eq!(JoinTarget::parse("canal"), Some(JoinTarget::Username("canal".to_string())));
+    assert_eq!(JoinTarget::parse("https://t.me/canal/"), Some(JoinTarget::Username("canal".to_string())));
+    assert_eq!(JoinTarget::parse("t.me/+AbC-123_x"), Some(JoinTarget::InviteHash("AbC-123_x".to_string())));
+    assert_eq!(JoinTarget::parse("https://telegram.me/joinchat/AAAAAE"), Some(JoinTarget::InviteHash("AAAAAE".to_string())));
+
+    assert_eq!(JoinTarget::parse("@"), None);
+    assert_eq!(JoinTarget::parse("canal-com-hifen"), None);
+    assert_eq!(JoinTarget::parse("https://t.me/+"), None);
+}
@@ -0,0 +1,184 @@
+use super::{FieldError, WatchlistConfig};
+use std::collections::HashMap;
+use std::path::Path;
+use toml::{Table, Value};
+
+// Campo do f1000.toml e a variável de ambiente equivalente, que tem prioridade sobre o arquivo
+pub const FIELDS: &[(&str, &str)] = &[
+    ("telegram.api_id", "TELEGRAM_API_ID"),
+    ("telegram.api_hash", "TELEGRAM_API_HASH"),
+    ("telegram.phone_number", "TELEGRAM_PHONE_NUMBER"),
+    ("telegram.session_path", "TELEGRAM_SESSION_PATH"),
+    ("telegram.chats", "TELEGRAM_CHATS"),
+    ("telegram.reconnect_base_delay_ms", "TELEGRAM_RECONNECT_BASE_DELAY_MS"),
+    ("telegram.reconnect_max_delay_ms", "TELEGRAM_RECONNECT_MAX_DELAY_MS"),
+    ("telegram.health_bind", "TELEGRAM_HEALTH_BIND"),
+    ("telegram.login_method", "TELEGRAM_LOGIN_METHOD"),
+    ("telegram.credentials", "TELEGRAM_CREDENTIALS"),
+    ("telegram.credentials_file", "TELEGRAM_CREDENTIALS_FILE"),
+    ("telegram.credentials_bind", "TELEGRAM_CREDENTIALS_BIND"),
+    ("telegram.login_attempts", "TELEGRAM_LOGIN_ATTEMPTS"),
+    ("database.url", "DATABASE_URL"),
+    ("backfill.until_date", "BACKFILL_UNTIL_DATE"),
+    ("backfill.until_message_id", "BACKFILL_UNTIL_MESSAGE_ID"),
+    ("media.enabled", "MEDIA_DOWNLOAD_ENABLED"),
+    ("media.storage_path", "MEDIA_STORAGE_PATH"),
+    ("media.max_file_size", "MEDIA_MAX_FILE_SIZE"),
+    ("media.max_concurrent_downloads", "MEDIA_MAX_CONCURRENT_DOWNLOADS"),
+    ("media.allowed_mime_types", "MEDIA_ALLOWED_MIME_TYPES"),
+    ("media.denied_mime_types", "MEDIA_DENIED_MIME_TYPES"),
+    ("media.chats", "MEDIA_CHATS"),
+    ("notify.webhook_url", "NOTIFY_WEBHOOK_URL"),
+    ("notify.telegram_chat", "NOTIFY_TELEGRAM_CHAT"),
+    ("notify.rate_limit_per_minute", "NOTIFY_RATE_LIMIT_PER_MINUTE"),
+    ("notify.max_attempts", "NOTIFY_MAX_ATTEMPTS"),
+    ("notify.retry_backoff_ms", "NOTIFY_RETRY_BACKOFF_MS"),
+    ("notify.smtp.host", "NOTIFY_SMTP_HOST"),
+    ("notify.smtp.port", "NOTIFY_SMTP_PORT"),
+    ("notify.smtp.tls", "NOTIFY_SMTP_TLS"),
+    ("notify.smtp.username", "NOTIFY_SMTP_USERNAME"),
+    ("notify.smtp.password", "NOTIFY_SMTP_PASSWORD"),
+    ("notify.smtp.from", "NOTIFY_SMTP_FROM"),
+    ("notify.smtp.to", "NOTIFY_SMTP_TO"),
+    ("notify.syslog.address", "NOTIFY_SYSLOG_ADDRESS"),
+    ("notify.syslog.protocol", "NOTIFY_SYSLOG_PROTOCOL"),
+    ("notify.syslog.app_name", "NOTIFY_SYSLOG_APP_NAME"),
+    ("export.misp.url", "MISP_URL"),
+    ("export.misp.api_key", "MISP_API_KEY"),
+    ("export.misp.verify_tls", "MISP_VERIFY_TLS"),
+    ("export.taxii.bind", "TAXII_BIND"),
+    ("export.taxii.public_url", "TAXII_PUBLIC_URL"),
+    ("export.taxii.username", "TAXII_USERNAME"),
+    ("export.taxii.password", "TAXII_PASSWORD"),
+    ("export.taxii.api_keys", "TAXII_API_KEYS"),
+    ("export.taxii.page_size", "TAXII_PAGE_SIZE"),
+    ("api.bind", "API_BIND"),
+    ("api.keys", "API_KEYS"),
+    ("policy.default_mode", "POLICY_DEFAULT_MODE"),
+    ("policy.chats", "POLICY_CHATS"),
+];
+
+const ACCOUNT_FIELDS: &[&str] = &["api_id", "api_hash", "phone_number", "session_path", "chats"];
+
+#[derive(Debug, Default)]
+pub struct ConfigFile {
+    values: HashMap<String, String>,
+    // Campo de origem das chaves montadas a partir de [[telegram.accounts]]
+    paths: HashMap<String, String>,
+    pub watchlists: Vec<WatchlistConfig>,
+}
+
+impl ConfigFile {
+    pub fn read(path: &Path) -> Result<Self, Vec<FieldError>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| vec![FieldError::new(path.display().to_string(), format!("não foi possível ler: {}", e))])?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Vec<FieldError>> {
+        let table: Table = text.parse()
+            .map_err(|e: toml::de::Error| vec![FieldError::new("f1000.toml", e.message().to_string())])?;
+
+        let mut file = ConfigFile::default();
+        let mut errors = Vec::new();
+        file.flatten("", &table, &mut errors);
+
+        if errors.is_empty() { Ok(file) } else { Err(errors) }
+    }
+
+    pub fn get(&self, env_name: &str) -> Option<&str> {
+        self.values.get(env_name).map(String::as_str)
+    }
+
+    pub fn path_of(&self, env_name: &str) -> Option<&str> {
+        self.paths.get(env_name).map(String::as_str)
+    }
+
+    fn flatten(&mut self, prefix: &str, table: &Table, errors: &mut Vec<FieldError>) {
+        for (key, value) in table {
+            let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+
+            if let Some((_, env_name)) = FIELDS.iter().find(|(field, _)| *field == path) {
+                match scalar(value) {
+                    Some(value) => { self.values.insert(env_name.to_string(), value); },
+                    None => errors.push(FieldError::new(path, "deve ser texto, número, booleano ou lista desses")),
+                }
+                continue;
+            }
+
+            match (path.as_str(), value) {
+                ("telegram.accounts", Value::Array(accounts)) => self.accounts(accounts, errors),
+                ("watchlists", Value::Array(rules)) => self.watchlists(rules, errors),
+                (_, Value::Table(table)) if FIELDS.iter().any(|(field, _)| field.starts_with(&format!("{}.", path))) => {
+                    self.flatten(&path, table, errors);
+                },
+                _ => errors.push(FieldError::new(path, "campo desconhecido")),
+            }
+        }
+    }
+
+    fn accounts(&mut self, accounts: &[Value], errors: &mut Vec<FieldError>) {
+        let mut ids = Vec::new();
+        for (index, account) in accounts.iter().enumerate() {
+            let path = format!("telegram.accounts[{}]", index);
+            let Some(account) = account.as_table() else {
+                errors.push(FieldError::new(path, "deve ser uma tabela [[telegram.accounts]]"));
+                continue;
+            };
+            let Some(id) = account.get("id").and_then(Value::as_str) else {
+                errors.push(FieldError::new(format!("{}.id", path), "é obrigatório"));
+                continue;
+            };
+
+            let prefix = super::account_prefix(id);
+            for (key, value) in account {
+                let field = format!("{}.{}", path, key);
+                if key == "id" {
+                    continue;
+                }
+                if !ACCOUNT_FIELDS.contains(&key.as_str()) {
+                    errors.push(FieldError::new(field, "campo desconhecido"));
+                    continue;
+                }
+                let env_name = format!("{}{}", prefix, key.to_uppercase());
+                match scalar(value) {
+                    Some(value) => { self.values.insert(env_name.clone(), value); },
+                    None => errors.push(FieldError::new(field.clone(), "deve ser texto, número ou lista")),
+                }
+                self.paths.insert(env_name, field);
+            }
+            ids.push(id.to_string());
+        }
+        self.values.insert("TELEGRAM_ACCOUNTS".to_string(), ids.join(","));
+        self.paths.insert("TELEGRAM_ACCOUNTS".to_string(), "telegram.accounts".to_string());
+    }
+
+    fn watchlists(&mut self, rules: &[Value], errors: &mut Vec<FieldError>) {
+        for (index, rule) in rules.iter().enumerate() {
+            let path = format!("watchlists[{}]", index);
+            match rule.clone().try_into::<WatchlistConfig>() {
+                Ok(rule) => self.watchlists.push(rule),
+                Err(e) => errors.push(FieldError::new(path, e.message().to_string())),
+            }
+        }
+    }
+}
+
+// Listas viram texto separado por vírgulas, como nas variáveis de ambiente
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Integer(value) => Some(value.to_string()),
+        Value::Float(value) => Some(value.to_string()),
+        Value::Boolean(value) => Some(value.to_string()),
+        Value::Datetime(value) => Some(value.to_string()),
+        Value::Array(items) => items.iter()
+            .map(|item| match item {
+                Value::Array(_) | Value::Table(_) => None,
+                item => scalar(item),
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|items| items.join(",")),
+        Value::Table(_) => None,
+    }
+}
@@ -1,6 +1,28 @@
-use sqlx::PgPool;
+use sqlx::{Executor, PgPool, Postgres, Transaction};
 use tracing::info;
 
+struct Migration {
+    version: i64,
+    name: &'static str,
+    sql: &'static str,
+}
+
+// Cada arquivo de migrations/ roda uma única vez, em ordem; as versões aplicadas ficam em schema_migrations
+const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "baseline", sql: include_str!("../../migrations/0001_baseline.sql") },
+    Migration { version: 2, name: "collection_pipeline", sql: include_str!("../../migrations/0002_collection_pipeline.sql") },
+];
+
+// Chave do pg_advisory_xact_lock: dois processos iniciando juntos não aplicam a mesma migração duas vezes
+const MIGRATION_LOCK: i64 = 0x6631_3030;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationOutcome {
+    Applied { from: i64, to: i64 },
+    AlreadyApplied { version: i64 },
+}
+
+#[derive(Clone)]
 pub struct Database {
     pool: PgPool,
 }
@@ -19,4 +41,64 @@ impl Database {
     pub fn get_pool(&self) -> &PgPool {
         &self.pool
     }
+    
+    pub async fn migrate(&self) -> Result<MigrationOutcome, sqlx::Error> {
+        // Tudo numa transação para não deixar o banco pela metade se uma instrução falhar
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)").bind(MIGRATION_LOCK).execute(&mut *transaction).await?;
+        transaction.execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                name VARCHAR(255) NOT NULL,
+                applied_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+            )",
+        ).await?;
+        
+        let mut version: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_migrations")
+            .fetch_one(&mut *transaction)
+            .await?;
+        // Bancos criados antes do controle de versões já têm o schema inicial; as migrações seguintes
+        // são idempotentes e completam o que faltar
+        if version == 0 {
+            let existing = sqlx::query_scalar!(
+                r#"SELECT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_schema = current_schema() AND table_name = 'telegram_messages') AS "existing!""#
+            )
+            .fetch_one(&mut *transaction)
+            .await?;
+            if existing {
+                record_migration(&mut transaction, &MIGRATIONS[0]).await?;
+                version = MIGRATIONS[0].version;
+            }
+        }
+        
+        let from = version;
+        for migration in MIGRATIONS.iter().filter(|migration| migration.version > from) {
+            transaction.execute(migration.sql).await?;
+            record_migration(&mut transaction, migration).await?;
+            info!("🗃️ Migração {} ({}) aplicada", migration.version, migration.name);
+            version = migration.version;
+        }
+        transaction.commit().await?;
+        
+        if version == from {
+            Ok(MigrationOutcome::AlreadyApplied { version })
+        } else {
+            Ok(MigrationOutcome::Applied { from, to: version })
+        }
+    }
+    
+    // Espera as conexões em uso voltarem ao pool antes de fechá-las
+    pub async fn close(&self) {
+        self.pool.close().await;
+        info!("🔌 Conexões com o banco de dados encerradas");
+    }
+}
+
+async fn record_migration(transaction: &mut Transaction<'_, Postgres>, migration: &Migration) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO schema_migrations (version, name) VALUES ($1, $2)")
+        .bind(migration.version)
+        .bind(migration.name)
+        .execute(&mut **transaction)
+        .await?;
+    Ok(())
 }
@@ -1,5 +1,12 @@
 pub mod connection;
 pub mod models;
+pub mod pagination;
 
-pub use connection::Database;
+pub use connection::{Database, MigrationOutcome};
 pub use models::*;
+pub use pagination::Cursor;
+
+#[cfg(test)]
+pub mod test_support;
+#[cfg(test)]
+mod tests;
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
+use super::Cursor;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TelegramUser {
@@ -32,6 +33,8 @@ pub struct TelegramGroup {
     pub is_restricted: bool,
     pub is_scam: bool,
     pub is_fake: bool,
+    // Conta dona da linha em chats privados e grupos básicos; NULL em canais e supergrupos
+    pub collector_account_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -49,6 +52,8 @@ pub struct TelegramMessage {
     pub forward_from_user_id: Option<Uuid>,
     pub forward_from_group_id: Option<Uuid>,
     pub forward_date: Option<DateTime<Utc>>,
+    pub forward_from_message_id: Option<i64>,
+    pub forward_sender_name: Option<String>,
     pub reply_to_message_id: Option<i64>,
     pub media_file_id: Option<String>,
     pub media_file_unique_id: Option<String>,
@@ -60,10 +65,29 @@ pub struct TelegramMessage {
     pub contact_phone_number: Option<String>,
     pub contact_first_name: Option<String>,
     pub contact_last_name: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deletion_source: Option<String>,
+    pub language: Option<String>,
+    pub collector_account_id: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
 
+#[derive(Debug, Clone, Default)]
+pub struct MessageSearch {
+    pub group_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub message_type: Option<String>,
+    pub has_media: Option<bool>,
+    pub text: Option<String>,
+    // Valor de IOC citado na mensagem (hash, domínio, carteira...), sem diferenciar maiúsculas
+    pub ioc: Option<String>,
+    pub before: Option<Cursor>,
+    pub limit: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewTelegramUser {
     pub telegram_user_id: i64,
@@ -90,6 +114,7 @@ pub struct NewTelegramGroup {
     pub is_restricted: bool,
     pub is_scam: bool,
     pub is_fake: bool,
+    pub collector_account_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,6 +129,8 @@ pub struct NewTelegramMessage {
     pub forward_from_user_id: Option<Uuid>,
     pub forward_from_group_id: Option<Uuid>,
     pub forward_date: Option<DateTime<Utc>>,
+    pub forward_from_message_id: Option<i64>,
+    pub forward_sender_name: Option<String>,
     pub reply_to_message_id: Option<i64>,
     pub media_file_id: Option<String>,
     pub media_file_unique_id: Option<String>,
@@ -115,6 +142,7 @@ pub struct NewTelegramMessage {
     pub contact_phone_number: Option<String>,
     pub contact_first_name: Option<String>,
     pub contact_last_name: Option<String>,
+    pub collector_account_id: Option<String>,
 }
 
 
@@ -126,7 +154,8 @@ impl TelegramUser {
         let id = Uuid::new_v4();
         let now = Utc::now();
         
-        sqlx::query!(
+        // Em caso de conflito o id retornado é o da linha já existente
+        let row = sqlx::query!(
             r#"
             INSERT INTO telegram_users 
             (id, telegram_user_id, username, first_name, last_name, phone_number, is_bot, is_verified, is_premium, language_code, created_at, updated_at)
@@ -160,7 +189,7 @@ impl TelegramUser {
         .await?;
         
         Ok(TelegramUser {
-            id,
+            id: row.id,
             telegram_user_id: new_user.telegram_user_id,
             username: new_user.username,
             first_name: new_user.first_name,
@@ -170,8 +199,8 @@ impl TelegramUser {
             is_verified: new_user.is_verified,
             is_premium: new_user.is_premium,
             language_code: new_user.language_code,
-            created_at: now,
-            updated_at: now,
+            created_at: row.created_at.unwrap_or(now),
+            updated_at: row.updated_at.unwrap_or(now),
         })
     }
     
@@ -197,10 +226,73 @@ impl TelegramUser {
             is_verified: r.is_verified.unwrap_or(false),
             is_premium: r.is_premium.unwrap_or(false),
             language_code: r.language_code,
-            created_at: r.created_at.unwrap_or_else(|| Utc::now()),
-            updated_at: r.updated_at.unwrap_or_else(|| Utc::now()),
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
         }))
     }
+    
+    pub async fn find_by_ids(
+        pool: &sqlx::PgPool,
+        ids: &[Uuid],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            "SELECT id, telegram_user_id, username, first_name, last_name, phone_number, is_bot, is_verified, is_premium, language_code, created_at, updated_at FROM telegram_users WHERE id = ANY($1)",
+            ids
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramUser {
+            id: r.id,
+            telegram_user_id: r.telegram_user_id,
+            username: r.username,
+            first_name: r.first_name,
+            last_name: r.last_name,
+            phone_number: r.phone_number,
+            is_bot: r.is_bot.unwrap_or(false),
+            is_verified: r.is_verified.unwrap_or(false),
+            is_premium: r.is_premium.unwrap_or(false),
+            language_code: r.language_code,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    // Página em ordem de cadastro; after é o cursor do último item da página anterior
+    pub async fn find_page(
+        pool: &sqlx::PgPool,
+        after: Option<Cursor>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, telegram_user_id, username, first_name, last_name, phone_number, is_bot, is_verified, is_premium, language_code, created_at, updated_at
+            FROM telegram_users
+            WHERE $1::timestamptz IS NULL OR (created_at, id) > ($1, $2)
+            ORDER BY created_at, id
+            LIMIT $3
+            "#,
+            after.map(|cursor| cursor.date),
+            after.map(|cursor| cursor.id),
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramUser {
+            id: r.id,
+            telegram_user_id: r.telegram_user_id,
+            username: r.username,
+            first_name: r.first_name,
+            last_name: r.last_name,
+            phone_number: r.phone_number,
+            is_bot: r.is_bot.unwrap_or(false),
+            is_verified: r.is_verified.unwrap_or(false),
+            is_premium: r.is_premium.unwrap_or(false),
+            language_code: r.language_code,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
 }
 
 impl TelegramGroup {
@@ -211,12 +303,13 @@ impl TelegramGroup {
         let id = Uuid::new_v4();
         let now = Utc::now();
         
-        sqlx::query!(
+        // Em caso de conflito o id retornado é o da linha já existente
+        let row = sqlx::query!(
             r#"
             INSERT INTO telegram_groups 
-            (id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
-            ON CONFLICT (telegram_chat_id) DO UPDATE SET
+            (id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, collector_account_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            ON CONFLICT (telegram_chat_id, COALESCE(collector_account_id, '')) DO UPDATE SET
                 chat_type = EXCLUDED.chat_type,
                 title = EXCLUDED.title,
                 username = EXCLUDED.username,
@@ -228,7 +321,7 @@ impl TelegramGroup {
                 is_scam = EXCLUDED.is_scam,
                 is_fake = EXCLUDED.is_fake,
                 updated_at = NOW()
-            RETURNING id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, created_at, updated_at
+            RETURNING id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, collector_account_id, created_at, updated_at
             "#,
             id,
             new_group.telegram_chat_id,
@@ -242,6 +335,7 @@ impl TelegramGroup {
             new_group.is_restricted,
             new_group.is_scam,
             new_group.is_fake,
+            new_group.collector_account_id,
             now,
             now
         )
@@ -249,7 +343,7 @@ impl TelegramGroup {
         .await?;
         
         Ok(TelegramGroup {
-            id,
+            id: row.id,
             telegram_chat_id: new_group.telegram_chat_id,
             chat_type: new_group.chat_type,
             title: new_group.title,
@@ -261,18 +355,52 @@ impl TelegramGroup {
             is_restricted: new_group.is_restricted,
             is_scam: new_group.is_scam,
             is_fake: new_group.is_fake,
-            created_at: now,
-            updated_at: now,
+            collector_account_id: new_group.collector_account_id,
+            created_at: row.created_at.unwrap_or(now),
+            updated_at: row.updated_at.unwrap_or(now),
         })
     }
     
+    // Canais e supergrupos são procurados com collector_account_id = None
     pub async fn find_by_telegram_id(
         pool: &sqlx::PgPool,
         telegram_chat_id: i64,
+        collector_account_id: Option<&str>,
     ) -> Result<Option<Self>, sqlx::Error> {
         let row = sqlx::query!(
-            "SELECT id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, created_at, updated_at FROM telegram_groups WHERE telegram_chat_id = $1",
-            telegram_chat_id
+            "SELECT id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, collector_account_id, created_at, updated_at FROM telegram_groups WHERE telegram_chat_id = $1 AND collector_account_id IS NOT DISTINCT FROM $2",
+            telegram_chat_id,
+            collector_account_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        
+        Ok(row.map(|r| TelegramGroup {
+            id: r.id,
+            telegram_chat_id: r.telegram_chat_id,
+            chat_type: r.chat_type,
+            title: r.title,
+            username: r.username,
+            description: r.description,
+            invite_link: r.invite_link,
+            member_count: r.member_count,
+            is_verified: r.is_verified.unwrap_or(false),
+            is_restricted: r.is_restricted.unwrap_or(false),
+            is_scam: r.is_scam.unwrap_or(false),
+            is_fake: r.is_fake.unwrap_or(false),
+            collector_account_id: r.collector_account_id,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
+        }))
+    }
+    
+    pub async fn find_by_username(
+        pool: &sqlx::PgPool,
+        username: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query!(
+            "SELECT id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, collector_account_id, created_at, updated_at FROM telegram_groups WHERE lower(username) = lower($1) ORDER BY collector_account_id NULLS FIRST, created_at LIMIT 1",
+            username.trim_start_matches('@')
         )
         .fetch_optional(pool)
         .await?;
@@ -290,10 +418,141 @@ impl TelegramGroup {
             is_restricted: r.is_restricted.unwrap_or(false),
             is_scam: r.is_scam.unwrap_or(false),
             is_fake: r.is_fake.unwrap_or(false),
-            created_at: r.created_at.unwrap_or_else(|| Utc::now()),
-            updated_at: r.updated_at.unwrap_or_else(|| Utc::now()),
+            collector_account_id: r.collector_account_id,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
         }))
     }
+    
+    pub async fn find_by_ids(
+        pool: &sqlx::PgPool,
+        ids: &[Uuid],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            "SELECT id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, collector_account_id, created_at, updated_at FROM telegram_groups WHERE id = ANY($1)",
+            ids
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramGroup {
+            id: r.id,
+            telegram_chat_id: r.telegram_chat_id,
+            chat_type: r.chat_type,
+            title: r.title,
+            username: r.username,
+            description: r.description,
+            invite_link: r.invite_link,
+            member_count: r.member_count,
+            is_verified: r.is_verified.unwrap_or(false),
+            is_restricted: r.is_restricted.unwrap_or(false),
+            is_scam: r.is_scam.unwrap_or(false),
+            is_fake: r.is_fake.unwrap_or(false),
+            collector_account_id: r.collector_account_id,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    
+    // Um chat privado ou grupo básico tem uma linha por conta que o coletou
+    pub async fn find_all_by_telegram_id(
+        pool: &sqlx::PgPool,
+        telegram_chat_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            "SELECT id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, collector_account_id, created_at, updated_at FROM telegram_groups WHERE telegram_chat_id = $1 ORDER BY collector_account_id NULLS FIRST, created_at",
+            telegram_chat_id
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramGroup {
+            id: r.id,
+            telegram_chat_id: r.telegram_chat_id,
+            chat_type: r.chat_type,
+            title: r.title,
+            username: r.username,
+            description: r.description,
+            invite_link: r.invite_link,
+            member_count: r.member_count,
+            is_verified: r.is_verified.unwrap_or(false),
+            is_restricted: r.is_restricted.unwrap_or(false),
+            is_scam: r.is_scam.unwrap_or(false),
+            is_fake: r.is_fake.unwrap_or(false),
+            collector_account_id: r.collector_account_id,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    
+    pub async fn find_all(pool: &sqlx::PgPool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            "SELECT id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, collector_account_id, created_at, updated_at FROM telegram_groups ORDER BY created_at, id"
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramGroup {
+            id: r.id,
+            telegram_chat_id: r.telegram_chat_id,
+            chat_type: r.chat_type,
+            title: r.title,
+            username: r.username,
+            description: r.description,
+            invite_link: r.invite_link,
+            member_count: r.member_count,
+            is_verified: r.is_verified.unwrap_or(false),
+            is_restricted: r.is_restricted.unwrap_or(false),
+            is_scam: r.is_scam.unwrap_or(false),
+            is_fake: r.is_fake.unwrap_or(false),
+            collector_account_id: r.collector_account_id,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    
+    pub async fn find_page(
+        pool: &sqlx::PgPool,
+        after: Option<Cursor>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, telegram_chat_id, chat_type, title, username, description, invite_link, member_count, is_verified, is_restricted, is_scam, is_fake, collector_account_id, created_at, updated_at
+            FROM telegram_groups
+            WHERE $1::timestamptz IS NULL OR (created_at, id) > ($1, $2)
+            ORDER BY created_at, id
+            LIMIT $3
+            "#,
+            after.map(|cursor| cursor.date),
+            after.map(|cursor| cursor.id),
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramGroup {
+            id: r.id,
+            telegram_chat_id: r.telegram_chat_id,
+            chat_type: r.chat_type,
+            title: r.title,
+            username: r.username,
+            description: r.description,
+            invite_link: r.invite_link,
+            member_count: r.member_count,
+            is_verified: r.is_verified.unwrap_or(false),
+            is_restricted: r.is_restricted.unwrap_or(false),
+            is_scam: r.is_scam.unwrap_or(false),
+            is_fake: r.is_fake.unwrap_or(false),
+            collector_account_id: r.collector_account_id,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    
+    pub fn message_link(&self, telegram_message_id: i64) -> Option<String> {
+        message_link(self.username.as_deref(), &self.chat_type, self.telegram_chat_id, telegram_message_id)
+    }
 }
 
 impl TelegramMessage {
@@ -303,12 +562,18 @@ impl TelegramMessage {
     ) -> Result<Self, sqlx::Error> {
         let id = Uuid::new_v4();
         let now = Utc::now();
+        // O idioma define a configuração do search_vector, montado pelo trigger no banco
+        let language = new_message.message_text.as_deref().and_then(crate::language::detect);
         
         sqlx::query!(
             r#"
             INSERT INTO telegram_messages 
-            (id, telegram_message_id, user_id, group_id, message_text, message_type, date, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            (id, telegram_message_id, user_id, group_id, message_text, message_type, date, edit_date,
+             forward_from_user_id, forward_from_group_id, forward_date, forward_from_message_id, forward_sender_name, reply_to_message_id,
+             media_file_id, media_file_unique_id, media_file_size, media_mime_type, media_file_name,
+             location_latitude, location_longitude, contact_phone_number, contact_first_name, contact_last_name,
+             language, search_config, collector_account_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20::float8, $21::float8, $22, $23, $24, $25, $26, $27, $28)
             ON CONFLICT (telegram_message_id, group_id) DO NOTHING
             RETURNING id
             "#,
@@ -319,6 +584,26 @@ impl TelegramMessage {
             new_message.message_text,
             new_message.message_type,
             new_message.date,
+            new_message.edit_date,
+            new_message.forward_from_user_id,
+            new_message.forward_from_group_id,
+            new_message.forward_date,
+            new_message.forward_from_message_id,
+            new_message.forward_sender_name,
+            new_message.reply_to_message_id,
+            new_message.media_file_id,
+            new_message.media_file_unique_id,
+            new_message.media_file_size,
+            new_message.media_mime_type,
+            new_message.media_file_name,
+            new_message.location_latitude,
+            new_message.location_longitude,
+            new_message.contact_phone_number,
+            new_message.contact_first_name,
+            new_message.contact_last_name,
+            language.as_ref().map(|language| language.code.clone()),
+            crate::language::search_config(language.as_ref()),
+            new_message.collector_account_id,
             now
         )
         .fetch_one(pool)
@@ -336,6 +621,8 @@ impl TelegramMessage {
             forward_from_user_id: new_message.forward_from_user_id,
             forward_from_group_id: new_message.forward_from_group_id,
             forward_date: new_message.forward_date,
+            forward_from_message_id: new_message.forward_from_message_id,
+            forward_sender_name: new_message.forward_sender_name,
             reply_to_message_id: new_message.reply_to_message_id,
             media_file_id: new_message.media_file_id,
             media_file_unique_id: new_message.media_file_unique_id,
@@ -347,9 +634,1549 @@ impl TelegramMessage {
             contact_phone_number: new_message.contact_phone_number,
             contact_first_name: new_message.contact_first_name,
             contact_last_name: new_message.contact_last_name,
+            deleted_at: None,
+            deletion_source: None,
+            language: language.map(|language| language.code),
+            collector_account_id: new_message.collector_account_id,
             created_at: now,
         })
     }
     
+    pub async fn find_by_id(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, telegram_message_id, user_id, group_id, message_text, message_type, date, edit_date,
+                   forward_from_user_id, forward_from_group_id, forward_date, forward_from_message_id, forward_sender_name, reply_to_message_id,
+                   media_file_id, media_file_unique_id, media_file_size, media_mime_type, media_file_name,
+                   location_latitude::float8 AS location_latitude, location_longitude::float8 AS location_longitude,
+                   contact_phone_number, contact_first_name, contact_last_name, deleted_at, deletion_source, language, collector_account_id, created_at
+            FROM telegram_messages WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+        
+        Ok(row.map(|r| TelegramMessage {
+            id: r.id,
+            telegram_message_id: r.telegram_message_id,
+            user_id: r.user_id,
+            group_id: r.group_id,
+            message_text: r.message_text,
+            message_type: r.message_type,
+            date: r.date,
+            edit_date: r.edit_date,
+            forward_from_user_id: r.forward_from_user_id,
+            forward_from_group_id: r.forward_from_group_id,
+            forward_date: r.forward_date,
+            forward_from_message_id: r.forward_from_message_id,
+            forward_sender_name: r.forward_sender_name,
+            reply_to_message_id: r.reply_to_message_id,
+            media_file_id: r.media_file_id,
+            media_file_unique_id: r.media_file_unique_id,
+            media_file_size: r.media_file_size,
+            media_mime_type: r.media_mime_type,
+            media_file_name: r.media_file_name,
+            location_latitude: r.location_latitude,
+            location_longitude: r.location_longitude,
+            contact_phone_number: r.contact_phone_number,
+            contact_first_name: r.contact_first_name,
+            contact_last_name: r.contact_last_name,
+            deleted_at: r.deleted_at,
+            deletion_source: r.deletion_source,
+            language: r.language,
+            collector_account_id: r.collector_account_id,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }))
+    }
+    
+    // Mensagens de um período (e opcionalmente de um grupo) em ordem cronológica, para exportação
+    pub async fn find_in_range(
+        pool: &sqlx::PgPool,
+        group_id: Option<Uuid>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, telegram_message_id, user_id, group_id, message_text, message_type, date, edit_date,
+                   forward_from_user_id, forward_from_group_id, forward_date, forward_from_message_id, forward_sender_name, reply_to_message_id,
+                   media_file_id, media_file_unique_id, media_file_size, media_mime_type, media_file_name,
+                   location_latitude::float8 AS location_latitude, location_longitude::float8 AS location_longitude,
+                   contact_phone_number, contact_first_name, contact_last_name, deleted_at, deletion_source, language, collector_account_id, created_at
+            FROM telegram_messages
+            WHERE ($1::uuid IS NULL OR group_id = $1)
+              AND ($2::timestamptz IS NULL OR date >= $2)
+              AND ($3::timestamptz IS NULL OR date < $3)
+            ORDER BY date, telegram_message_id
+            "#,
+            group_id,
+            since,
+            until
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramMessage {
+            id: r.id,
+            telegram_message_id: r.telegram_message_id,
+            user_id: r.user_id,
+            group_id: r.group_id,
+            message_text: r.message_text,
+            message_type: r.message_type,
+            date: r.date,
+            edit_date: r.edit_date,
+            forward_from_user_id: r.forward_from_user_id,
+            forward_from_group_id: r.forward_from_group_id,
+            forward_date: r.forward_date,
+            forward_from_message_id: r.forward_from_message_id,
+            forward_sender_name: r.forward_sender_name,
+            reply_to_message_id: r.reply_to_message_id,
+            media_file_id: r.media_file_id,
+            media_file_unique_id: r.media_file_unique_id,
+            media_file_size: r.media_file_size,
+            media_mime_type: r.media_mime_type,
+            media_file_name: r.media_file_name,
+            location_latitude: r.location_latitude,
+            location_longitude: r.location_longitude,
+            contact_phone_number: r.contact_phone_number,
+            contact_first_name: r.contact_first_name,
+            contact_last_name: r.contact_last_name,
+            deleted_at: r.deleted_at,
+            deletion_source: r.deletion_source,
+            language: r.language,
+            collector_account_id: r.collector_account_id,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    
+    // Busca da API: filtros opcionais, mais recentes primeiro; before é o cursor do último item da página anterior
+    pub async fn search(
+        pool: &sqlx::PgPool,
+        search: &MessageSearch,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT m.id, m.telegram_message_id, m.user_id, m.group_id, m.message_text, m.message_type, m.date, m.edit_date,
+                   m.forward_from_user_id, m.forward_from_group_id, m.forward_date, m.forward_from_message_id, m.forward_sender_name, m.reply_to_message_id,
+                   m.media_file_id, m.media_file_unique_id, m.media_file_size, m.media_mime_type, m.media_file_name,
+                   m.location_latitude::float8 AS location_latitude, m.location_longitude::float8 AS location_longitude,
+                   m.contact_phone_number, m.contact_first_name, m.contact_last_name, m.deleted_at, m.deletion_source, m.language, m.collector_account_id, m.created_at
+            FROM telegram_messages m
+            WHERE ($1::uuid IS NULL OR m.group_id = $1)
+              AND ($2::uuid IS NULL OR m.user_id = $2)
+              AND ($3::timestamptz IS NULL OR m.date >= $3)
+              AND ($4::timestamptz IS NULL OR m.date < $4)
+              AND ($5::text IS NULL OR m.message_type = $5)
+              AND ($6::bool IS NULL OR (m.media_file_id IS NOT NULL) = $6)
+              AND ($7::text IS NULL OR m.search_vector @@ multilingual_tsquery($7))
+              AND ($8::text IS NULL OR EXISTS (
+                  SELECT 1 FROM message_iocs mi JOIN iocs i ON i.id = mi.ioc_id
+                  WHERE mi.message_id = m.id AND lower(i.value) = lower($8)
+              ))
+              AND ($9::timestamptz IS NULL OR (m.date, m.id) < ($9, $10))
+            ORDER BY m.date DESC, m.id DESC
+            LIMIT $11
+            "#,
+            search.group_id,
+            search.user_id,
+            search.since,
+            search.until,
+            search.message_type,
+            search.has_media,
+            search.text,
+            search.ioc,
+            search.before.map(|cursor| cursor.date),
+            search.before.map(|cursor| cursor.id),
+            search.limit
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramMessage {
+            id: r.id,
+            telegram_message_id: r.telegram_message_id,
+            user_id: r.user_id,
+            group_id: r.group_id,
+            message_text: r.message_text,
+            message_type: r.message_type,
+            date: r.date,
+            edit_date: r.edit_date,
+            forward_from_user_id: r.forward_from_user_id,
+            forward_from_group_id: r.forward_from_group_id,
+            forward_date: r.forward_date,
+            forward_from_message_id: r.forward_from_message_id,
+            forward_sender_name: r.forward_sender_name,
+            reply_to_message_id: r.reply_to_message_id,
+            media_file_id: r.media_file_id,
+            media_file_unique_id: r.media_file_unique_id,
+            media_file_size: r.media_file_size,
+            media_mime_type: r.media_mime_type,
+            media_file_name: r.media_file_name,
+            location_latitude: r.location_latitude,
+            location_longitude: r.location_longitude,
+            contact_phone_number: r.contact_phone_number,
+            contact_first_name: r.contact_first_name,
+            contact_last_name: r.contact_last_name,
+            deleted_at: r.deleted_at,
+            deletion_source: r.deletion_source,
+            language: r.language,
+            collector_account_id: r.collector_account_id,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    
+    pub async fn find_id_by_telegram_id(
+        pool: &sqlx::PgPool,
+        group_id: Uuid,
+        telegram_message_id: i64,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let row = sqlx::query!(
+            "SELECT id FROM telegram_messages WHERE group_id = $1 AND telegram_message_id = $2",
+            group_id,
+            telegram_message_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        
+        Ok(row.map(|r| r.id))
+    }
+    
+    pub async fn update_edit_date(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        edit_date: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE telegram_messages SET edit_date = $2 WHERE id = $1 AND (edit_date IS NULL OR edit_date < $2)",
+            id,
+            edit_date
+        )
+        .execute(pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn mark_deleted(
+        pool: &sqlx::PgPool,
+        telegram_chat_id: Option<i64>,
+        telegram_message_ids: &[i64],
+        deletion_source: &str,
+        collector_account_id: Option<&str>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = match telegram_chat_id {
+            Some(telegram_chat_id) => sqlx::query!(
+                r#"
+                UPDATE telegram_messages m SET deleted_at = NOW(), deletion_source = $3
+                FROM telegram_groups g
+                WHERE m.group_id = g.id AND g.telegram_chat_id = $1 AND g.chat_type IN ('channel', 'supergroup')
+                  AND m.telegram_message_id = ANY($2) AND m.deleted_at IS NULL
+                "#,
+                telegram_chat_id,
+                telegram_message_ids,
+                deletion_source
+            )
+            .execute(pool)
+            .await?,
+            // Fora de canais o Telegram não informa o chat: os IDs são únicos por conta, então só
+            // valem para as mensagens coletadas pela conta que recebeu a exclusão
+            None => sqlx::query!(
+                r#"
+                UPDATE telegram_messages m SET deleted_at = NOW(), deletion_source = $2
+                FROM telegram_groups g
+                WHERE m.group_id = g.id AND g.chat_type IN ('private', 'group')
+                  AND m.telegram_message_id = ANY($1) AND m.deleted_at IS NULL
+                  AND m.collector_account_id IS NOT DISTINCT FROM $3
+                "#,
+                telegram_message_ids,
+                deletion_source,
+                collector_account_id
+            )
+            .execute(pool)
+            .await?,
+        };
+        
+        Ok(result.rows_affected())
+    }
+    
+    pub async fn edit_timeline(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+    ) -> Result<Option<MessageTimeline>, sqlx::Error> {
+        let Some(original) = Self::find_by_id(pool, id).await? else {
+            return Ok(None);
+        };
+        let versions = TelegramMessageVersion::find_by_message_id(pool, id).await?;
+        
+        Ok(Some(MessageTimeline { original, versions }))
+    }
+}
+
+// Resultado da busca textual: trecho com os termos entre <mark> e relevância calculada pelo Postgres
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageHit {
+    pub message: TelegramMessage,
+    pub rank: f32,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextSearch {
+    pub query: String,
+    pub group_id: Option<Uuid>,
+    pub language: Option<String>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl MessageHit {
+    // A consulta é interpretada em todas as configurações e cada mensagem casa com a do próprio idioma
+    pub async fn search(
+        pool: &sqlx::PgPool,
+        search: &TextSearch,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        if search.query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let rows = sqlx::query!(
+            r#"
+            WITH q AS (SELECT multilingual_tsquery($1) AS query)
+            SELECT m.id, m.telegram_message_id, m.user_id, m.group_id, m.message_text, m.message_type, m.date, m.edit_date,
+                   m.forward_from_user_id, m.forward_from_group_id, m.forward_date, m.forward_from_message_id, m.forward_sender_name, m.reply_to_message_id,
+                   m.media_file_id, m.media_file_unique_id, m.media_file_size, m.media_mime_type, m.media_file_name,
+                   m.location_latitude::float8 AS location_latitude, m.location_longitude::float8 AS location_longitude,
+                   m.contact_phone_number, m.contact_first_name, m.contact_last_name, m.deleted_at, m.deletion_source, m.language, m.collector_account_id, m.created_at,
+                   ts_rank_cd(m.search_vector, q.query, 32) AS "rank!",
+                   ts_headline(m.search_config::regconfig, coalesce(m.message_text, ''), q.query,
+                       'StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15, MaxFragments=2, FragmentDelimiter=" … "') AS "snippet!"
+            FROM telegram_messages m, q
+            WHERE m.search_vector @@ q.query
+              AND ($2::uuid IS NULL OR m.group_id = $2)
+              AND ($3::text IS NULL OR m.language = $3)
+            ORDER BY "rank!" DESC, m.date DESC, m.id
+            LIMIT $4 OFFSET $5
+            "#,
+            search.query,
+            search.group_id,
+            search.language,
+            search.limit,
+            search.offset
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| MessageHit {
+            message: TelegramMessage {
+                id: r.id,
+                telegram_message_id: r.telegram_message_id,
+                user_id: r.user_id,
+                group_id: r.group_id,
+                message_text: r.message_text,
+                message_type: r.message_type,
+                date: r.date,
+                edit_date: r.edit_date,
+                forward_from_user_id: r.forward_from_user_id,
+                forward_from_group_id: r.forward_from_group_id,
+                forward_date: r.forward_date,
+                forward_from_message_id: r.forward_from_message_id,
+                forward_sender_name: r.forward_sender_name,
+                reply_to_message_id: r.reply_to_message_id,
+                media_file_id: r.media_file_id,
+                media_file_unique_id: r.media_file_unique_id,
+                media_file_size: r.media_file_size,
+                media_mime_type: r.media_mime_type,
+                media_file_name: r.media_file_name,
+                location_latitude: r.location_latitude,
+                location_longitude: r.location_longitude,
+                contact_phone_number: r.contact_phone_number,
+                contact_first_name: r.contact_first_name,
+                contact_last_name: r.contact_last_name,
+                deleted_at: r.deleted_at,
+                deletion_source: r.deletion_source,
+                language: r.language,
+                collector_account_id: r.collector_account_id,
+                created_at: r.created_at.unwrap_or_else(Utc::now),
+            },
+            rank: r.rank,
+            snippet: r.snippet,
+        }).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedMessage {
+    pub message_id: Uuid,
+    pub telegram_message_id: i64,
+    pub group_id: Option<Uuid>,
+    pub message_text: Option<String>,
+    pub date: DateTime<Utc>,
+    pub deleted_at: DateTime<Utc>,
+    pub deletion_source: Option<String>,
+    pub seconds_alive: f64,
+}
+
+impl DeletedMessage {
+    pub async fn find_deleted_within(
+        pool: &sqlx::PgPool,
+        group_id: Uuid,
+        max_lifetime: chrono::Duration,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, telegram_message_id, group_id, message_text, date, deleted_at AS "deleted_at!", deletion_source,
+                   EXTRACT(EPOCH FROM deleted_at - date)::float8 AS "seconds_alive!"
+            FROM telegram_messages
+            WHERE group_id = $1 AND deleted_at IS NOT NULL
+              AND deleted_at - date <= make_interval(secs => $2)
+            ORDER BY deleted_at DESC
+            "#,
+            group_id,
+            max_lifetime.num_seconds() as f64
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| DeletedMessage {
+            message_id: r.id,
+            telegram_message_id: r.telegram_message_id,
+            group_id: r.group_id,
+            message_text: r.message_text,
+            date: r.date,
+            deleted_at: r.deleted_at,
+            deletion_source: r.deletion_source,
+            seconds_alive: r.seconds_alive,
+        }).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramMessageVersion {
+    pub id: Uuid,
+    pub message_id: Uuid,
+    pub message_text: Option<String>,
+    pub edit_date: DateTime<Utc>,
+    pub entities: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewTelegramMessageVersion {
+    pub message_id: Uuid,
+    pub message_text: Option<String>,
+    pub edit_date: DateTime<Utc>,
+    pub entities: serde_json::Value,
+}
+
+// Mensagem como foi capturada originalmente seguida de cada edição, da mais antiga para a mais nova
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageTimeline {
+    pub original: TelegramMessage,
+    pub versions: Vec<TelegramMessageVersion>,
+}
+
+impl TelegramMessageVersion {
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        new_version: NewTelegramMessageVersion,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO telegram_message_versions 
+            (id, message_id, message_text, edit_date, entities, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (message_id, edit_date) DO NOTHING
+            RETURNING id
+            "#,
+            id,
+            new_version.message_id,
+            new_version.message_text,
+            new_version.edit_date,
+            new_version.entities,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+        
+        Ok(TelegramMessageVersion {
+            id,
+            message_id: new_version.message_id,
+            message_text: new_version.message_text,
+            edit_date: new_version.edit_date,
+            entities: new_version.entities,
+            created_at: now,
+        })
+    }
+    
+    pub async fn find_by_message_id(
+        pool: &sqlx::PgPool,
+        message_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            "SELECT id, message_id, message_text, edit_date, entities, created_at FROM telegram_message_versions WHERE message_id = $1 ORDER BY edit_date",
+            message_id
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramMessageVersion {
+            id: r.id,
+            message_id: r.message_id,
+            message_text: r.message_text,
+            edit_date: r.edit_date,
+            entities: r.entities,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramMedia {
+    pub id: Uuid,
+    pub message_id: Uuid,
+    pub sha256: String,
+    pub sha1: String,
+    pub md5: String,
+    pub file_size: i64,
+    pub mime_type: Option<String>,
+    pub file_name: Option<String>,
+    pub storage_path: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewTelegramMedia {
+    pub message_id: Uuid,
+    pub sha256: String,
+    pub sha1: String,
+    pub md5: String,
+    pub file_size: i64,
+    pub mime_type: Option<String>,
+    pub file_name: Option<String>,
+    pub storage_path: String,
+}
+
+impl TelegramMedia {
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        new_media: NewTelegramMedia,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO telegram_media 
+            (id, message_id, sha256, sha1, md5, file_size, mime_type, file_name, storage_path, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (message_id) DO NOTHING
+            RETURNING id
+            "#,
+            id,
+            new_media.message_id,
+            new_media.sha256,
+            new_media.sha1,
+            new_media.md5,
+            new_media.file_size,
+            new_media.mime_type,
+            new_media.file_name,
+            new_media.storage_path,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+        
+        Ok(TelegramMedia {
+            id,
+            message_id: new_media.message_id,
+            sha256: new_media.sha256,
+            sha1: new_media.sha1,
+            md5: new_media.md5,
+            file_size: new_media.file_size,
+            mime_type: new_media.mime_type,
+            file_name: new_media.file_name,
+            storage_path: new_media.storage_path,
+            created_at: now,
+        })
+    }
+    
+    pub async fn find_by_hash(
+        pool: &sqlx::PgPool,
+        hash: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let hash = hash.to_lowercase();
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, message_id, sha256, sha1, md5, file_size, mime_type, file_name, storage_path, created_at
+            FROM telegram_media WHERE sha256 = $1 OR sha1 = $1 OR md5 = $1
+            ORDER BY created_at
+            "#,
+            hash
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| TelegramMedia {
+            id: r.id,
+            message_id: r.message_id,
+            sha256: r.sha256,
+            sha1: r.sha1,
+            md5: r.md5,
+            file_size: r.file_size,
+            mime_type: r.mime_type,
+            file_name: r.file_name,
+            storage_path: r.storage_path,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillState {
+    pub group_id: Uuid,
+    pub oldest_message_id: Option<i64>,
+    pub oldest_message_date: Option<DateTime<Utc>>,
+    pub messages_processed: i64,
+    pub completed: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BackfillState {
+    pub async fn find_by_group_id(
+        pool: &sqlx::PgPool,
+        group_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query!(
+            "SELECT group_id, oldest_message_id, oldest_message_date, messages_processed, completed, updated_at FROM backfill_state WHERE group_id = $1",
+            group_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        
+        Ok(row.map(|r| BackfillState {
+            group_id: r.group_id,
+            oldest_message_id: r.oldest_message_id,
+            oldest_message_date: r.oldest_message_date,
+            messages_processed: r.messages_processed,
+            completed: r.completed,
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
+        }))
+    }
+    
+    pub async fn save(
+        pool: &sqlx::PgPool,
+        state: &BackfillState,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO backfill_state 
+            (group_id, oldest_message_id, oldest_message_date, messages_processed, completed)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (group_id) DO UPDATE SET
+                oldest_message_id = EXCLUDED.oldest_message_id,
+                oldest_message_date = EXCLUDED.oldest_message_date,
+                messages_processed = EXCLUDED.messages_processed,
+                completed = EXCLUDED.completed
+            "#,
+            state.group_id,
+            state.oldest_message_id,
+            state.oldest_message_date,
+            state.messages_processed,
+            state.completed
+        )
+        .execute(pool)
+        .await?;
+        
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSyncState {
+    pub group_id: Uuid,
+    pub last_message_id: i64,
+    pub last_message_date: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ChatSyncState {
+    pub async fn find_by_group_id(
+        pool: &sqlx::PgPool,
+        group_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query!(
+            "SELECT group_id, last_message_id, last_message_date, updated_at FROM chat_sync_state WHERE group_id = $1",
+            group_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        
+        Ok(row.map(|r| ChatSyncState {
+            group_id: r.group_id,
+            last_message_id: r.last_message_id,
+            last_message_date: r.last_message_date,
+            updated_at: r.updated_at.unwrap_or_else(Utc::now),
+        }))
+    }
+    
+    pub async fn advance(
+        pool: &sqlx::PgPool,
+        group_id: Uuid,
+        message_id: i64,
+        message_date: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        // A marca só avança: mensagens antigas (backfill, recuperação) não a fazem recuar
+        sqlx::query!(
+            r#"
+            INSERT INTO chat_sync_state (group_id, last_message_id, last_message_date)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (group_id) DO UPDATE SET
+                last_message_id = EXCLUDED.last_message_id,
+                last_message_date = EXCLUDED.last_message_date
+            WHERE chat_sync_state.last_message_id < EXCLUDED.last_message_id
+            "#,
+            group_id,
+            message_id,
+            message_date
+        )
+        .execute(pool)
+        .await?;
+        
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageEntity {
+    pub id: Uuid,
+    pub message_id: Uuid,
+    pub entity_type: String,
+    pub offset: i32,
+    pub length: i32,
+    pub url: Option<String>,
+    pub mentioned_user_id: Option<i64>,
+    pub language: Option<String>,
+    pub entity_text: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewMessageEntity {
+    pub message_id: Uuid,
+    pub entity_type: String,
+    pub offset: i32,
+    pub length: i32,
+    pub url: Option<String>,
+    pub mentioned_user_id: Option<i64>,
+    pub language: Option<String>,
+    pub entity_text: Option<String>,
+}
+
+impl MessageEntity {
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        new_entity: NewMessageEntity,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO message_entities 
+            (id, message_id, entity_type, entity_offset, entity_length, url, mentioned_user_id, language, entity_text, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (message_id, entity_type, entity_offset, entity_length) DO NOTHING
+            RETURNING id
+            "#,
+            id,
+            new_entity.message_id,
+            new_entity.entity_type,
+            new_entity.offset,
+            new_entity.length,
+            new_entity.url,
+            new_entity.mentioned_user_id,
+            new_entity.language,
+            new_entity.entity_text,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+        
+        Ok(MessageEntity {
+            id,
+            message_id: new_entity.message_id,
+            entity_type: new_entity.entity_type,
+            offset: new_entity.offset,
+            length: new_entity.length,
+            url: new_entity.url,
+            mentioned_user_id: new_entity.mentioned_user_id,
+            language: new_entity.language,
+            entity_text: new_entity.entity_text,
+            created_at: now,
+        })
+    }
+    
+    pub async fn find_by_message_id(
+        pool: &sqlx::PgPool,
+        message_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, message_id, entity_type, entity_offset, entity_length, url, mentioned_user_id, language, entity_text, created_at
+            FROM message_entities WHERE message_id = $1
+            ORDER BY entity_offset, entity_length
+            "#,
+            message_id
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| MessageEntity {
+            id: r.id,
+            message_id: r.message_id,
+            entity_type: r.entity_type,
+            offset: r.entity_offset,
+            length: r.entity_length,
+            url: r.url,
+            mentioned_user_id: r.mentioned_user_id,
+            language: r.language,
+            entity_text: r.entity_text,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ioc {
+    pub id: Uuid,
+    pub ioc_type: String,
+    pub value: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub sighting_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageIoc {
+    pub message_id: Uuid,
+    pub ioc: Ioc,
+}
+
+impl Ioc {
+    // Cada mensagem conta uma única vez: reprocessar (edição, backfill) não infla as contagens
+    pub async fn record_sighting(
+        pool: &sqlx::PgPool,
+        message_id: Uuid,
+        ioc_type: &str,
+        value: &str,
+        seen_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        let ioc = sqlx::query!(
+            r#"
+            INSERT INTO iocs (id, ioc_type, value, first_seen, last_seen)
+            VALUES ($1, $2, $3, $4, $4)
+            ON CONFLICT (ioc_type, value) DO UPDATE SET ioc_type = EXCLUDED.ioc_type
+            RETURNING id
+            "#,
+            Uuid::new_v4(),
+            ioc_type,
+            value,
+            seen_at
+        )
+        .fetch_one(pool)
+        .await?;
+        
+        let linked = sqlx::query!(
+            "INSERT INTO message_iocs (message_id, ioc_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            message_id,
+            ioc.id
+        )
+        .execute(pool)
+        .await?
+        .rows_affected() > 0;
+        
+        let row = sqlx::query!(
+            r#"
+            UPDATE iocs SET
+                sighting_count = sighting_count + CASE WHEN $2 THEN 1 ELSE 0 END,
+                first_seen = CASE WHEN $2 THEN LEAST(first_seen, $3) ELSE first_seen END,
+                last_seen = CASE WHEN $2 THEN GREATEST(last_seen, $3) ELSE last_seen END
+            WHERE id = $1
+            RETURNING id, ioc_type, value, first_seen, last_seen, sighting_count, created_at
+            "#,
+            ioc.id,
+            linked,
+            seen_at
+        )
+        .fetch_one(pool)
+        .await?;
+        
+        Ok(Ioc {
+            id: row.id,
+            ioc_type: row.ioc_type,
+            value: row.value,
+            first_seen: row.first_seen,
+            last_seen: row.last_seen,
+            sighting_count: row.sighting_count,
+            created_at: row.created_at.unwrap_or_else(Utc::now),
+        })
+    }
+    
+    pub async fn find_by_value(
+        pool: &sqlx::PgPool,
+        value: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, ioc_type, value, first_seen, last_seen, sighting_count, created_at
+            FROM iocs WHERE lower(value) = lower($1)
+            ORDER BY ioc_type
+            "#,
+            value
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| Ioc {
+            id: r.id,
+            ioc_type: r.ioc_type,
+            value: r.value,
+            first_seen: r.first_seen,
+            last_seen: r.last_seen,
+            sighting_count: r.sighting_count,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    
+    pub async fn find_by_message_id(
+        pool: &sqlx::PgPool,
+        message_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT i.id, i.ioc_type, i.value, i.first_seen, i.last_seen, i.sighting_count, i.created_at
+            FROM iocs i
+            JOIN message_iocs mi ON mi.ioc_id = i.id
+            WHERE mi.message_id = $1
+            ORDER BY i.ioc_type, i.value
+            "#,
+            message_id
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| Ioc {
+            id: r.id,
+            ioc_type: r.ioc_type,
+            value: r.value,
+            first_seen: r.first_seen,
+            last_seen: r.last_seen,
+            sighting_count: r.sighting_count,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    
+    pub async fn find_by_message_ids(
+        pool: &sqlx::PgPool,
+        message_ids: &[Uuid],
+    ) -> Result<Vec<MessageIoc>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT mi.message_id, i.id, i.ioc_type, i.value, i.first_seen, i.last_seen, i.sighting_count, i.created_at
+            FROM message_iocs mi
+            JOIN iocs i ON i.id = mi.ioc_id
+            WHERE mi.message_id = ANY($1)
+            ORDER BY i.ioc_type, i.value
+            "#,
+            message_ids
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| MessageIoc {
+            message_id: r.message_id,
+            ioc: Ioc {
+                id: r.id,
+                ioc_type: r.ioc_type,
+                value: r.value,
+                first_seen: r.first_seen,
+                last_seen: r.last_seen,
+                sighting_count: r.sighting_count,
+                created_at: r.created_at.unwrap_or_else(Utc::now),
+            },
+        }).collect())
+    }
+}
+
+// IOC visto num conjunto de mensagens; date_added é o primeiro vínculo com alguma delas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectedIoc {
+    pub ioc: Ioc,
+    pub date_added: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CollectedIocQuery {
+    pub group_id: Option<Uuid>,
+    pub added_after: Option<DateTime<Utc>>,
+    // Desempate para paginar IOCs com o mesmo date_added
+    pub after_id: Option<Uuid>,
+    pub ids: Option<Vec<Uuid>>,
+    pub ioc_types: Option<Vec<String>>,
+    pub exclude_ioc_types: Option<Vec<String>>,
+    pub limit: i64,
+}
+
+impl CollectedIoc {
+    pub async fn find(
+        pool: &sqlx::PgPool,
+        query: &CollectedIocQuery,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT i.id, i.ioc_type, i.value, i.first_seen, i.last_seen, i.sighting_count, i.created_at,
+                   MIN(mi.created_at) AS "date_added!"
+            FROM iocs i
+            JOIN message_iocs mi ON mi.ioc_id = i.id
+            JOIN telegram_messages m ON m.id = mi.message_id
+            WHERE ($1::uuid IS NULL OR m.group_id = $1)
+              AND ($4::uuid[] IS NULL OR i.id = ANY($4))
+              AND ($5::text[] IS NULL OR i.ioc_type = ANY($5))
+              AND ($6::text[] IS NULL OR i.ioc_type <> ALL($6))
+            GROUP BY i.id
+            HAVING $2::timestamptz IS NULL
+                OR MIN(mi.created_at) > $2
+                OR (MIN(mi.created_at) = $2 AND $3::uuid IS NOT NULL AND i.id > $3)
+            ORDER BY "date_added!", i.id
+            LIMIT $7
+            "#,
+            query.group_id,
+            query.added_after,
+            query.after_id,
+            query.ids.as_deref(),
+            query.ioc_types.as_deref(),
+            query.exclude_ioc_types.as_deref(),
+            query.limit
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| CollectedIoc {
+            ioc: Ioc {
+                id: r.id,
+                ioc_type: r.ioc_type,
+                value: r.value,
+                first_seen: r.first_seen,
+                last_seen: r.last_seen,
+                sighting_count: r.sighting_count,
+                created_at: r.created_at.unwrap_or_else(Utc::now),
+            },
+            date_added: r.date_added,
+        }).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistRule {
+    pub id: Uuid,
+    pub name: String,
+    pub pattern: String,
+    pub match_type: String,
+    pub max_distance: Option<i32>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewWatchlistRule {
+    pub name: String,
+    pub pattern: String,
+    pub match_type: String,
+    pub max_distance: Option<i32>,
+}
+
+impl WatchlistRule {
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        new_rule: NewWatchlistRule,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO watchlists (id, name, pattern, match_type, max_distance, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            id,
+            new_rule.name,
+            new_rule.pattern,
+            new_rule.match_type,
+            new_rule.max_distance,
+            now
+        )
+        .execute(pool)
+        .await?;
+        
+        Ok(WatchlistRule {
+            id,
+            name: new_rule.name,
+            pattern: new_rule.pattern,
+            match_type: new_rule.match_type,
+            max_distance: new_rule.max_distance,
+            enabled: true,
+            created_at: now,
+        })
+    }
+    
+    // Regras vindas do f1000.toml: atualiza pelo nome e cria as que faltam; as cadastradas por outros meios ficam intactas
+    pub async fn sync(pool: &sqlx::PgPool, rules: &[NewWatchlistRule]) -> Result<usize, sqlx::Error> {
+        let mut created = 0;
+        let mut tx = pool.begin().await?;
+        
+        for rule in rules {
+            let updated = sqlx::query!(
+                r#"
+                UPDATE watchlists SET pattern = $2, match_type = $3, max_distance = $4, enabled = TRUE
+                WHERE name = $1
+                "#,
+                rule.name,
+                rule.pattern,
+                rule.match_type,
+                rule.max_distance
+            )
+            .execute(&mut *tx)
+            .await?;
+            
+            if updated.rows_affected() == 0 {
+                sqlx::query!(
+                    "INSERT INTO watchlists (name, pattern, match_type, max_distance) VALUES ($1, $2, $3, $4)",
+                    rule.name,
+                    rule.pattern,
+                    rule.match_type,
+                    rule.max_distance
+                )
+                .execute(&mut *tx)
+                .await?;
+                created += 1;
+            }
+        }
+        
+        tx.commit().await?;
+        Ok(created)
+    }
+    
+    pub async fn find_enabled(pool: &sqlx::PgPool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, name, pattern, match_type, max_distance, enabled, created_at
+            FROM watchlists WHERE enabled
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| WatchlistRule {
+            id: r.id,
+            name: r.name,
+            pattern: r.pattern,
+            match_type: r.match_type,
+            max_distance: r.max_distance,
+            enabled: r.enabled,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatPolicyRule {
+    pub id: Uuid,
+    pub selector_type: String,
+    pub selector: String,
+    pub mode: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewChatPolicyRule {
+    pub selector_type: String,
+    pub selector: String,
+    pub mode: String,
+}
+
+impl ChatPolicyRule {
+    // Um seletor tem uma única política: cadastrar de novo troca o modo
+    pub async fn upsert(
+        pool: &sqlx::PgPool,
+        new_rule: NewChatPolicyRule,
+    ) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO chat_policies (selector_type, selector, mode)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (selector_type, selector) DO UPDATE SET mode = EXCLUDED.mode
+            RETURNING id, selector_type, selector, mode, created_at
+            "#,
+            new_rule.selector_type,
+            new_rule.selector,
+            new_rule.mode
+        )
+        .fetch_one(pool)
+        .await?;
+        
+        Ok(ChatPolicyRule {
+            id: row.id,
+            selector_type: row.selector_type,
+            selector: row.selector,
+            mode: row.mode,
+            created_at: row.created_at.unwrap_or_else(Utc::now),
+        })
+    }
+    
+    pub async fn find_all(pool: &sqlx::PgPool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, selector_type, selector, mode, created_at
+            FROM chat_policies
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| ChatPolicyRule {
+            id: r.id,
+            selector_type: r.selector_type,
+            selector: r.selector,
+            mode: r.mode,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+    
+    pub async fn delete(pool: &sqlx::PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM chat_policies WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: Uuid,
+    pub watchlist_id: Uuid,
+    pub message_id: Uuid,
+    pub group_id: Option<Uuid>,
+    pub match_start: i32,
+    pub match_end: i32,
+    pub matched_text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAlert {
+    pub watchlist_id: Uuid,
+    pub message_id: Uuid,
+    pub group_id: Option<Uuid>,
+    pub match_start: i32,
+    pub match_end: i32,
+    pub matched_text: String,
+}
+
+impl Alert {
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        new_alert: NewAlert,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO alerts (id, watchlist_id, message_id, group_id, match_start, match_end, matched_text, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (watchlist_id, message_id, match_start, match_end) DO NOTHING
+            RETURNING id
+            "#,
+            id,
+            new_alert.watchlist_id,
+            new_alert.message_id,
+            new_alert.group_id,
+            new_alert.match_start,
+            new_alert.match_end,
+            new_alert.matched_text,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+        
+        Ok(Alert {
+            id,
+            watchlist_id: new_alert.watchlist_id,
+            message_id: new_alert.message_id,
+            group_id: new_alert.group_id,
+            match_start: new_alert.match_start,
+            match_end: new_alert.match_end,
+            matched_text: new_alert.matched_text,
+            created_at: now,
+        })
+    }
+    
+    pub async fn find_by_message_id(
+        pool: &sqlx::PgPool,
+        message_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, watchlist_id, message_id, group_id, match_start, match_end, matched_text, created_at
+            FROM alerts WHERE message_id = $1
+            ORDER BY match_start
+            "#,
+            message_id
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| Alert {
+            id: r.id,
+            watchlist_id: r.watchlist_id,
+            message_id: r.message_id,
+            group_id: r.group_id,
+            match_start: r.match_start,
+            match_end: r.match_end,
+            matched_text: r.matched_text,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+}
+
+// Alerta com o contexto necessário para notificar fora do banco (regra, chat, remetente, texto)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertDetails {
+    pub alert_id: Uuid,
+    pub rule_name: String,
+    pub match_type: String,
+    pub match_start: i32,
+    pub match_end: i32,
+    pub matched_text: String,
+    pub message_id: Uuid,
+    pub telegram_message_id: i64,
+    pub message_text: Option<String>,
+    pub message_date: DateTime<Utc>,
+    pub telegram_chat_id: Option<i64>,
+    pub group_title: Option<String>,
+    pub group_username: Option<String>,
+    pub chat_type: Option<String>,
+    pub sender_telegram_id: Option<i64>,
+    pub sender_username: Option<String>,
+    pub sender_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AlertDetails {
+    pub async fn find_by_alert_id(
+        pool: &sqlx::PgPool,
+        alert_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT a.id, w.name, w.match_type, a.match_start, a.match_end, a.matched_text,
+                   m.id AS message_id, m.telegram_message_id, m.message_text, m.date,
+                   g.telegram_chat_id AS "telegram_chat_id?", g.title AS "group_title?",
+                   g.username AS "group_username?", g.chat_type AS "chat_type?",
+                   u.telegram_user_id AS "sender_telegram_id?", u.username AS "sender_username?",
+                   NULLIF(TRIM(CONCAT(u.first_name, ' ', u.last_name)), '') AS sender_name,
+                   a.created_at
+            FROM alerts a
+            JOIN watchlists w ON w.id = a.watchlist_id
+            JOIN telegram_messages m ON m.id = a.message_id
+            LEFT JOIN telegram_groups g ON g.id = a.group_id
+            LEFT JOIN telegram_users u ON u.id = m.user_id
+            WHERE a.id = $1
+            "#,
+            alert_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        
+        Ok(row.map(|r| AlertDetails {
+            alert_id: r.id,
+            rule_name: r.name,
+            match_type: r.match_type,
+            match_start: r.match_start,
+            match_end: r.match_end,
+            matched_text: r.matched_text,
+            message_id: r.message_id,
+            telegram_message_id: r.telegram_message_id,
+            message_text: r.message_text,
+            message_date: r.date,
+            telegram_chat_id: r.telegram_chat_id,
+            group_title: r.group_title,
+            group_username: r.group_username,
+            chat_type: r.chat_type,
+            sender_telegram_id: r.sender_telegram_id,
+            sender_username: r.sender_username,
+            sender_name: r.sender_name,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }))
+    }
+    
+    pub fn message_link(&self) -> Option<String> {
+        message_link(
+            self.group_username.as_deref(),
+            self.chat_type.as_deref()?,
+            self.telegram_chat_id?,
+            self.telegram_message_id,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationDelivery {
+    pub id: Uuid,
+    pub alert_id: Uuid,
+    pub notifier: String,
+    pub status: String,
+    pub attempts: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl NotificationDelivery {
+    pub async fn record(
+        pool: &sqlx::PgPool,
+        alert_id: Uuid,
+        notifier: &str,
+        status: &str,
+        attempts: i32,
+        error: Option<String>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO notification_deliveries (id, alert_id, notifier, status, attempts, error, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            id,
+            alert_id,
+            notifier,
+            status,
+            attempts,
+            error,
+            now
+        )
+        .execute(pool)
+        .await?;
+        
+        Ok(NotificationDelivery {
+            id,
+            alert_id,
+            notifier: notifier.to_string(),
+            status: status.to_string(),
+            attempts,
+            error,
+            created_at: now,
+        })
+    }
+    
+    pub async fn find_by_alert_id(
+        pool: &sqlx::PgPool,
+        alert_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, alert_id, notifier, status, attempts, error, created_at
+            FROM notification_deliveries WHERE alert_id = $1
+            ORDER BY created_at
+            "#,
+            alert_id
+        )
+        .fetch_all(pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|r| NotificationDelivery {
+            id: r.id,
+            alert_id: r.alert_id,
+            notifier: r.notifier,
+            status: r.status,
+            attempts: r.attempts,
+            error: r.error,
+            created_at: r.created_at.unwrap_or_else(Utc::now),
+        }).collect())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollectionStats {
+    pub groups: i64,
+    pub users: i64,
+    pub messages: i64,
+    pub deleted_messages: i64,
+    pub media: i64,
+    pub iocs: i64,
+    pub alerts: i64,
+    pub first_message_date: Option<DateTime<Utc>>,
+    pub last_message_date: Option<DateTime<Utc>>,
+}
+
+impl CollectionStats {
+    pub async fn load(pool: &sqlx::PgPool) -> Result<Self, sqlx::Error> {
+        let r = sqlx::query!(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM telegram_groups) AS "groups!",
+                (SELECT COUNT(*) FROM telegram_users) AS "users!",
+                (SELECT COUNT(*) FROM telegram_messages) AS "messages!",
+                (SELECT COUNT(*) FROM telegram_messages WHERE deleted_at IS NOT NULL) AS "deleted_messages!",
+                (SELECT COUNT(*) FROM telegram_media) AS "media!",
+                (SELECT COUNT(*) FROM iocs) AS "iocs!",
+                (SELECT COUNT(*) FROM alerts) AS "alerts!",
+                (SELECT MIN(date) FROM telegram_messages) AS first_message_date,
+                (SELECT MAX(date) FROM telegram_messages) AS last_message_date
+            "#
+        )
+        .fetch_one(pool)
+        .await?;
+        
+        Ok(CollectionStats {
+            groups: r.groups,
+            users: r.users,
+            messages: r.messages,
+            deleted_messages: r.deleted_messages,
+            media: r.media,
+            iocs: r.iocs,
+            alerts: r.alerts,
+            first_message_date: r.first_message_date,
+            last_message_date: r.last_message_date,
+        })
+    }
+}
+
+// Link t.me para a mensagem: público pelo username, privado via /c/; só supergrupos e canais têm link,
+// já que em chats privados o username é do usuário e não leva à conversa
+pub fn message_link(
+    username: Option<&str>,
+    chat_type: &str,
+    telegram_chat_id: i64,
+    telegram_message_id: i64,
+) -> Option<String> {
+    if !matches!(chat_type, "supergroup" | "channel") {
+        return None;
+    }
+    match username {
+        Some(username) => Some(format!("https://t.me/{}/{}", username, telegram_message_id)),
+        None => Some(format!("https://t.me/c/{}/{}", telegram_chat_id, telegram_message_id)),
+    }
 }
@@ -0,0 +1,62 @@
+use super::*;
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+// Os testes usam o banco apontado por DATABASE_URL (o mesmo exigido pelo sqlx::query!)
+pub async fn test_database() -> Database {
+    dotenv::dotenv().ok();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL precisa estar definido para os testes");
+    Database::new(&url).await.expect("falha ao conectar no banco de testes")
+}
+
+pub fn random_telegram_id() -> i64 {
+    (Uuid::new_v4().as_u64_pair().0 >> 12) as i64
+}
+
+pub async fn create_group(pool: &sqlx::PgPool, chat_type: &str) -> TelegramGroup {
+    TelegramGroup::create(pool, NewTelegramGroup {
+        telegram_chat_id: random_telegram_id(),
+        chat_type: chat_type.to_string(),
+        title: Some("Round trip".to_string()),
+        username: None,
+        description: None,
+        invite_link: None,
+        member_count: None,
+        is_verified: false,
+        is_restricted: false,
+        is_scam: false,
+        is_fake: false,
+        collector_account_id: None,
+    })
+    .await
+    .expect("falha ao criar grupo")
+}
+
+pub fn minimal_message(group_id: Uuid) -> NewTelegramMessage {
+    NewTelegramMessage {
+        telegram_message_id: random_telegram_id(),
+        user_id: None,
+        group_id: Some(group_id),
+        message_text: None,
+        message_type: "unknown".to_string(),
+        date: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+        edit_date: None,
+        forward_from_user_id: None,
+        forward_from_group_id: None,
+        forward_date: None,
+        forward_from_message_id: None,
+        forward_sender_name: None,
+        reply_to_message_id: None,
+        media_file_id: None,
+        media_file_unique_id: None,
+        media_file_size: None,
+        media_mime_type: None,
+        media_file_name: None,
+        location_latitude: None,
+        location_longitude: None,
+        contact_phone_number: None,
+        contact_first_name: None,
+        contact_last_name: None,
+        collector_account_id: None,
+    }
+}
@@ -0,0 +1,196 @@
+use crate::config::{parse_date, MispConfig};
+use crate::db::{Database, MessageIoc, TelegramGroup, TelegramMessage, TelegramUser};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+pub mod misp;
+pub mod stix;
+
+// Namespace fixo para IDs determinísticos: reexportar o mesmo dado gera os mesmos objetos na TIP
+const NAMESPACE: Uuid = Uuid::from_u128(0x6f1a2b3c_4d5e_4f60_8a71_b2c3d4e5f607);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Stix,
+    Misp,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportFilter {
+    pub group: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub filter: ExportFilter,
+    pub output: Option<PathBuf>,
+    pub per_day: bool,
+    pub push: bool,
+}
+
+impl ExportOptions {
+    // Uso: export stix|misp [--since AAAA-MM-DD] [--until AAAA-MM-DD] [--group ID|@username] [--output arquivo]
+    //     misp aceita ainda --per-day (um evento por grupo e dia) e --push (envia ao MISP configurado)
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut args = args.iter();
+        let format = match args.next().map(String::as_str) {
+            Some("stix") => ExportFormat::Stix,
+            Some("misp") => ExportFormat::Misp,
+            Some(other) => return Err(format!("formato de exportação desconhecido: {}", other)),
+            None => return Err("informe o formato de exportação (stix ou misp)".to_string()),
+        };
+
+        let mut options = ExportOptions { format, filter: ExportFilter::default(), output: None, per_day: false, push: false };
+        while let Some(flag) = args.next() {
+            match (flag.as_str(), format) {
+                ("--per-day", ExportFormat::Misp) => {
+                    options.per_day = true;
+                    continue;
+                },
+                ("--push", ExportFormat::Misp) => {
+                    options.push = true;
+                    continue;
+                },
+                _ => {},
+            }
+
+            let value = args.next().ok_or_else(|| format!("{} precisa de um valor", flag))?;
+            match flag.as_str() {
+                "--since" => options.filter.since = Some(parse_date(value)
+                    .ok_or_else(|| format!("data inválida em --since: {}", value))?),
+                "--until" => {
+                    let until = parse_date(value).ok_or_else(|| format!("data inválida em --until: {}", value))?;
+                    // Uma data sem horário inclui o dia inteiro
+                    options.filter.until = Some(if value.len() == 10 { until + chrono::Duration::days(1) } else { until });
+                },
+                "--group" => options.filter.group = Some(value.clone()),
+                "--output" | "-o" => options.output = Some(PathBuf::from(value)),
+                _ => return Err(format!("opção desconhecida: {}", flag)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+// Linhas do banco que entram numa exportação, já filtradas
+#[derive(Debug, Clone, Default)]
+pub struct ExportData {
+    pub groups: Vec<TelegramGroup>,
+    pub users: Vec<TelegramUser>,
+    pub messages: Vec<TelegramMessage>,
+    pub iocs: Vec<MessageIoc>,
+}
+
+impl ExportData {
+    pub async fn load(database: &Database, filter: &ExportFilter) -> Result<Self, Box<dyn std::error::Error>> {
+        let pool = database.get_pool();
+
+        let group_id = match &filter.group {
+            Some(group) => Some(resolve_group(database, group).await?.id),
+            None => None,
+        };
+
+        let messages = TelegramMessage::find_in_range(pool, group_id, filter.since, filter.until).await?;
+        let message_ids: Vec<Uuid> = messages.iter().map(|message| message.id).collect();
+        let group_ids: Vec<Uuid> = messages.iter().filter_map(|message| message.group_id).collect::<HashSet<_>>().into_iter().collect();
+        let user_ids: Vec<Uuid> = messages.iter().filter_map(|message| message.user_id).collect::<HashSet<_>>().into_iter().collect();
+
+        Ok(ExportData {
+            groups: TelegramGroup::find_by_ids(pool, &group_ids).await?,
+            users: TelegramUser::find_by_ids(pool, &user_ids).await?,
+            iocs: crate::db::Ioc::find_by_message_ids(pool, &message_ids).await?,
+            messages,
+        })
+    }
+}
+
+pub async fn resolve_group(database: &Database, group: &str) -> Result<TelegramGroup, Box<dyn std::error::Error>> {
+    let pool = database.get_pool();
+    let found = if let Ok(id) = group.parse::<Uuid>() {
+        TelegramGroup::find_by_ids(pool, &[id]).await?.pop()
+    } else if let Ok(telegram_chat_id) = group.parse::<i64>() {
+        // Chat privado ou grupo básico coletado por várias contas: cada cópia é escolhida pelo UUID
+        let mut groups = TelegramGroup::find_all_by_telegram_id(pool, telegram_chat_id).await?;
+        if groups.len() > 1 {
+            let copies: Vec<String> = groups.iter()
+                .map(|group| format!("{} ({})", group.id, group.collector_account_id.as_deref().unwrap_or("-")))
+                .collect();
+            return Err(format!("o chat {} foi coletado por mais de uma conta; use o UUID: {}", group, copies.join(", ")).into());
+        }
+        groups.pop()
+    } else {
+        TelegramGroup::find_by_username(pool, group).await?
+    };
+
+    found.ok_or_else(|| format!("grupo não encontrado: {}", group).into())
+}
+
+pub async fn run(database: &Database, options: &ExportOptions, misp: Option<&MispConfig>) -> Result<(), Box<dyn std::error::Error>> {
+    // Falha antes de consultar o banco se o push não tem para onde ir
+    let misp_client = match (options.push, misp) {
+        (false, _) => None,
+        (true, Some(config)) => Some(misp::MispClient::new(config).map_err(|e| e as Box<dyn std::error::Error>)?),
+        (true, None) => return Err("--push exige MISP_URL e MISP_API_KEY configurados".into()),
+    };
+
+    let data = ExportData::load(database, &options.filter).await?;
+    info!("📦 Exportando {} mensagens de {} grupos ({} IOCs)", data.messages.len(), data.groups.len(), data.iocs.len());
+
+    let document = match options.format {
+        ExportFormat::Stix => stix::build_bundle(&data),
+        ExportFormat::Misp => {
+            let events = misp::build_events(&data, options.per_day);
+            info!("🗂️ {} eventos MISP gerados", events.len());
+            if let Some(client) = &misp_client {
+                push_events(client, &events).await?;
+            }
+            serde_json::Value::Array(events)
+        },
+    };
+
+    // Com --push a saída só é escrita se um arquivo foi pedido
+    if misp_client.is_some() && options.output.is_none() {
+        return Ok(());
+    }
+
+    let json = serde_json::to_string_pretty(&document)?;
+    match &options.output {
+        Some(path) => {
+            tokio::fs::write(path, json).await?;
+            info!("✅ Exportação salva em {}", path.display());
+        },
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+async fn push_events(client: &misp::MispClient, events: &[serde_json::Value]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut failures = 0;
+    for event in events {
+        let info = event["Event"]["info"].as_str().unwrap_or_default();
+        match client.push(event).await {
+            Ok(misp::PushOutcome::Created) => info!("📤 Evento MISP criado: {}", info),
+            Ok(misp::PushOutcome::Updated) => info!("🔄 Evento MISP atualizado: {}", info),
+            Err(e) => {
+                warn!("❌ Erro ao enviar evento MISP {}: {}", info, e);
+                failures += 1;
+            },
+        }
+    }
+
+    if failures > 0 {
+        return Err(format!("{} de {} eventos não foram enviados ao MISP", failures, events.len()).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,192 @@
+use super::{ExportData, NAMESPACE};
+use crate::config::MispConfig;
+use crate::db::{Ioc, TelegramGroup, TelegramMessage};
+use chrono::NaiveDate;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+use uuid::Uuid;
+
+pub type MispError = Box<dyn std::error::Error + Send + Sync>;
+
+// Um evento por grupo ou, com per_day, um por grupo e dia (UTC)
+pub fn build_events(data: &ExportData, per_day: bool) -> Vec<Value> {
+    let groups: HashMap<Uuid, &TelegramGroup> = data.groups.iter().map(|group| (group.id, group)).collect();
+    let mut iocs_by_message: HashMap<Uuid, Vec<&Ioc>> = HashMap::new();
+    for link in &data.iocs {
+        iocs_by_message.entry(link.message_id).or_default().push(&link.ioc);
+    }
+
+    let mut buckets: BTreeMap<(Uuid, Option<NaiveDate>), Vec<&TelegramMessage>> = BTreeMap::new();
+    for message in &data.messages {
+        let Some(group_id) = message.group_id.filter(|id| groups.contains_key(id)) else {
+            continue;
+        };
+        let day = per_day.then(|| message.date.date_naive());
+        buckets.entry((group_id, day)).or_default().push(message);
+    }
+
+    buckets.into_iter()
+        .filter_map(|((group_id, day), messages)| event(groups[&group_id], day, &messages, &iocs_by_message))
+        .collect()
+}
+
+pub fn event_uuid(group: &TelegramGroup, day: Option<NaiveDate>) -> Uuid {
+    let key = match day {
+        Some(day) => format!("misp|{}|{}", group.id, day),
+        None => format!("misp|{}", group.id),
+    };
+    Uuid::new_v5(&NAMESPACE, key.as_bytes())
+}
+
+fn event(
+    group: &TelegramGroup,
+    day: Option<NaiveDate>,
+    messages: &[&TelegramMessage],
+    iocs_by_message: &HashMap<Uuid, Vec<&Ioc>>,
+) -> Option<Value> {
+    let uuid = event_uuid(group, day);
+    let mut seen = HashSet::new();
+    let mut attributes = Vec::new();
+
+    // O MISP recusa atributos repetidos no mesmo evento: cada IOC entra uma vez, comentado com a primeira mensagem
+    for message in messages {
+        for ioc in iocs_by_message.get(&message.id).into_iter().flatten() {
+            if seen.insert(ioc.id) {
+                attributes.push(attribute(&uuid, ioc, group, message));
+            }
+        }
+    }
+    if attributes.is_empty() {
+        return None;
+    }
+
+    let name = chat_name(group);
+    let date = day.unwrap_or_else(|| messages[0].date.date_naive());
+    let info = match day {
+        Some(day) => format!("Telegram: {} ({})", name, day),
+        None => format!("Telegram: {}", name),
+    };
+
+    let mut tags = vec![
+        json!({ "name": "source:telegram" }),
+        json!({ "name": format!("telegram:chat-id=\"{}\"", group.telegram_chat_id) }),
+        json!({ "name": format!("telegram:chat-type=\"{}\"", group.chat_type) }),
+    ];
+    if let Some(username) = &group.username {
+        tags.push(json!({ "name": format!("telegram:chat=\"@{}\"", username) }));
+    }
+
+    Some(json!({
+        "Event": {
+            "uuid": uuid,
+            "info": info,
+            "date": date.to_string(),
+            "threat_level_id": "4",
+            "analysis": "0",
+            "distribution": "0",
+            "published": false,
+            "Tag": tags,
+            "Attribute": attributes,
+        }
+    }))
+}
+
+fn attribute(event_uuid: &Uuid, ioc: &Ioc, group: &TelegramGroup, message: &TelegramMessage) -> Value {
+    let (attribute_type, category, to_ids) = attribute_type(&ioc.ioc_type);
+
+    let mut comment = message.message_text.clone().unwrap_or_default();
+    if let Some(link) = group.message_link(message.telegram_message_id) {
+        comment.push_str(&format!("\n\n{}", link));
+    }
+
+    // Sem "timestamp": o MISP só atualiza atributos cujo timestamp recebido seja mais novo que o salvo
+    json!({
+        "uuid": Uuid::new_v5(event_uuid, ioc.id.as_bytes()),
+        "type": attribute_type,
+        "category": category,
+        "value": ioc.value,
+        "to_ids": to_ids,
+        "comment": comment.trim(),
+        "first_seen": ioc.first_seen.to_rfc3339(),
+        "last_seen": ioc.last_seen.max(ioc.first_seen).to_rfc3339(),
+    })
+}
+
+pub fn attribute_type(ioc_type: &str) -> (&'static str, &'static str, bool) {
+    match ioc_type {
+        "ipv4" | "ipv6" => ("ip-dst", "Network activity", true),
+        "domain" | "onion" => ("domain", "Network activity", true),
+        "url" => ("url", "Network activity", true),
+        "telegram_invite" => ("url", "Social network", false),
+        "email" => ("email-src", "Payload delivery", true),
+        "md5" => ("md5", "Payload delivery", true),
+        "sha1" => ("sha1", "Payload delivery", true),
+        "sha256" => ("sha256", "Payload delivery", true),
+        "cve" => ("vulnerability", "External analysis", false),
+        "bitcoin_wallet" => ("btc", "Financial fraud", true),
+        "monero_wallet" => ("xmr", "Financial fraud", true),
+        // O MISP não tem tipo próprio para Ethereum
+        _ => ("text", "Financial fraud", false),
+    }
+}
+
+fn chat_name(group: &TelegramGroup) -> String {
+    group.title.clone()
+        .or_else(|| group.username.as_ref().map(|username| format!("@{}", username)))
+        .unwrap_or_else(|| group.telegram_chat_id.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    Created,
+    Updated,
+}
+
+pub struct MispClient {
+    url: String,
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl MispClient {
+    pub fn new(config: &MispConfig) -> Result<Self, MispError> {
+        Ok(Self {
+            url: config.url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .danger_accept_invalid_certs(!config.verify_tls)
+                .build()?,
+        })
+    }
+
+    // O UUID do evento é determinístico: se já existe no MISP, a reexportação vira edição
+    pub async fn push(&self, event: &Value) -> Result<PushOutcome, MispError> {
+        let uuid = event["Event"]["uuid"].as_str().ok_or("evento MISP sem uuid")?;
+
+        let existing = self.request(reqwest::Method::GET, &format!("events/view/{}", uuid)).send().await?;
+        let (path, outcome) = match existing.status() {
+            status if status.is_success() => (format!("events/edit/{}", uuid), PushOutcome::Updated),
+            StatusCode::NOT_FOUND | StatusCode::FORBIDDEN => ("events/add".to_string(), PushOutcome::Created),
+            status => return Err(format!("MISP respondeu {} ao consultar o evento {}", status, uuid).into()),
+        };
+
+        let response = self.request(reqwest::Method::POST, &path).json(event).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("MISP respondeu {} em {}: {}", status, path, body).into());
+        }
+
+        Ok(outcome)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, format!("{}/{}", self.url, path))
+            .header("Authorization", &self.api_key)
+            .header("Accept", "application/json")
+    }
+}
@@ -0,0 +1,246 @@
+use super::{ExportData, NAMESPACE};
+use crate::db::{Ioc, TelegramGroup, TelegramMessage, TelegramUser};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+pub fn build_bundle(data: &ExportData) -> Value {
+    let mut bundle = BundleBuilder::default();
+    let producer_id = producer_id();
+    bundle.push(producer_identity());
+
+    let groups: HashMap<Uuid, &TelegramGroup> = data.groups.iter().map(|group| (group.id, group)).collect();
+    let users: HashMap<Uuid, &TelegramUser> = data.users.iter().map(|user| (user.id, user)).collect();
+    let mut iocs_by_message: HashMap<Uuid, Vec<&Ioc>> = HashMap::new();
+    for link in &data.iocs {
+        iocs_by_message.entry(link.message_id).or_default().push(&link.ioc);
+    }
+
+    for message in &data.messages {
+        let group = message.group_id.and_then(|id| groups.get(&id).copied());
+        let user = message.user_id.and_then(|id| users.get(&id).copied());
+        let group_ref = group.map(|group| group_object(group, &producer_id)).map(|object| bundle.push(object));
+        let user_ref = user.map(|user| threat_actor_object(user, &producer_id)).map(|object| bundle.push(object));
+
+        if let (Some(user_ref), Some(group_ref)) = (&user_ref, &group_ref) {
+            bundle.relate(user_ref, "related-to", group_ref, &producer_id, message.date);
+        }
+
+        let mut object_refs: Vec<String> = group_ref.iter().chain(user_ref.iter()).cloned().collect();
+        for ioc in iocs_by_message.get(&message.id).into_iter().flatten() {
+            let ioc_ref = bundle.push(ioc_object(ioc, &producer_id));
+            if let Some(actor_ref) = user_ref.as_ref().or(group_ref.as_ref()) {
+                bundle.relate(&ioc_ref, "related-to", actor_ref, &producer_id, ioc.first_seen);
+            }
+            object_refs.push(ioc_ref);
+        }
+        if object_refs.is_empty() {
+            object_refs.push(producer_id.clone());
+        }
+
+        bundle.push(note_object(message, group, user, object_refs, &producer_id));
+    }
+
+    json!({
+        "type": "bundle",
+        "id": format!("bundle--{}", Uuid::new_v4()),
+        "objects": bundle.objects,
+    })
+}
+
+#[derive(Default)]
+struct BundleBuilder {
+    objects: Vec<Value>,
+    ids: HashSet<String>,
+}
+
+impl BundleBuilder {
+    // Objetos repetidos (mesmo grupo, usuário ou IOC em várias mensagens) entram uma única vez
+    fn push(&mut self, object: Value) -> String {
+        let id = object["id"].as_str().unwrap_or_default().to_string();
+        if self.ids.insert(id.clone()) {
+            self.objects.push(object);
+        }
+        id
+    }
+
+    fn relate(&mut self, source: &str, relationship_type: &str, target: &str, producer_id: &str, date: DateTime<Utc>) {
+        let id = format!("relationship--{}", Uuid::new_v5(&NAMESPACE, format!("{}|{}|{}", source, relationship_type, target).as_bytes()));
+        self.push(json!({
+            "type": "relationship",
+            "spec_version": "2.1",
+            "id": id,
+            "created_by_ref": producer_id,
+            "created": timestamp(date),
+            "modified": timestamp(date),
+            "relationship_type": relationship_type,
+            "source_ref": source,
+            "target_ref": target,
+        }));
+    }
+}
+
+pub fn producer_id() -> String {
+    format!("identity--{}", Uuid::new_v5(&NAMESPACE, b"f1000"))
+}
+
+pub fn producer_identity() -> Value {
+    let created = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).single().unwrap_or_else(Utc::now);
+    json!({
+        "type": "identity",
+        "spec_version": "2.1",
+        "id": producer_id(),
+        "created": timestamp(created),
+        "modified": timestamp(created),
+        "name": "F1000",
+        "identity_class": "system",
+    })
+}
+
+fn group_object(group: &TelegramGroup, producer_id: &str) -> Value {
+    let name = group.title.clone()
+        .or_else(|| group.username.as_ref().map(|username| format!("@{}", username)))
+        .unwrap_or_else(|| group.telegram_chat_id.to_string());
+
+    let mut object = json!({
+        "type": "identity",
+        "spec_version": "2.1",
+        "id": format!("identity--{}", group.id),
+        "created_by_ref": producer_id,
+        "created": timestamp(group.created_at),
+        "modified": timestamp(group.updated_at),
+        "name": name,
+        "identity_class": "group",
+        "external_references": [telegram_reference(group.telegram_chat_id, group.username.as_deref())],
+        "x_telegram_chat_type": group.chat_type,
+    });
+    if let Some(description) = &group.description {
+        object["description"] = json!(description);
+    }
+    object
+}
+
+fn threat_actor_object(user: &TelegramUser, producer_id: &str) -> Value {
+    let full_name = [user.first_name.as_deref(), user.last_name.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let name = Some(full_name).filter(|name| !name.is_empty())
+        .or_else(|| user.username.as_ref().map(|username| format!("@{}", username)))
+        .unwrap_or_else(|| user.telegram_user_id.to_string());
+
+    let mut object = json!({
+        "type": "threat-actor",
+        "spec_version": "2.1",
+        "id": format!("threat-actor--{}", user.id),
+        "created_by_ref": producer_id,
+        "created": timestamp(user.created_at),
+        "modified": timestamp(user.updated_at),
+        "name": name,
+        "threat_actor_types": ["unknown"],
+        "external_references": [telegram_reference(user.telegram_user_id, user.username.as_deref())],
+    });
+    if let Some(username) = &user.username {
+        object["aliases"] = json!([format!("@{}", username)]);
+    }
+    object
+}
+
+fn note_object(
+    message: &TelegramMessage,
+    group: Option<&TelegramGroup>,
+    user: Option<&TelegramUser>,
+    object_refs: Vec<String>,
+    producer_id: &str,
+) -> Value {
+    let chat = group.and_then(|group| group.title.clone()).unwrap_or_else(|| "chat desconhecido".to_string());
+    let content = message.message_text.clone().unwrap_or_else(|| format!("[{}]", message.message_type));
+
+    let mut object = json!({
+        "type": "note",
+        "spec_version": "2.1",
+        "id": format!("note--{}", message.id),
+        "created_by_ref": producer_id,
+        "created": timestamp(message.date),
+        "modified": timestamp(message.edit_date.unwrap_or(message.date).max(message.date)),
+        "abstract": format!("Mensagem {} em {}", message.telegram_message_id, chat),
+        "content": content,
+        "object_refs": object_refs,
+        "x_telegram_message_id": message.telegram_message_id,
+        "x_telegram_message_type": message.message_type,
+    });
+    if let Some(user) = user {
+        let author = user.username.as_ref().map(|username| format!("@{}", username))
+            .unwrap_or_else(|| user.telegram_user_id.to_string());
+        object["authors"] = json!([author]);
+    }
+    if let Some(link) = group.and_then(|group| group.message_link(message.telegram_message_id)) {
+        object["external_references"] = json!([{ "source_name": "telegram", "url": link }]);
+    }
+    object
+}
+
+pub fn ioc_object(ioc: &Ioc, producer_id: &str) -> Value {
+    // CVE não é observável: vira vulnerability em vez de indicator
+    if ioc.ioc_type == "cve" {
+        return json!({
+            "type": "vulnerability",
+            "spec_version": "2.1",
+            "id": format!("vulnerability--{}", ioc.id),
+            "created_by_ref": producer_id,
+            "created": timestamp(ioc.created_at),
+            "modified": timestamp(ioc.last_seen),
+            "name": ioc.value,
+            "external_references": [{ "source_name": "cve", "external_id": ioc.value }],
+        });
+    }
+
+    json!({
+        "type": "indicator",
+        "spec_version": "2.1",
+        "id": format!("indicator--{}", ioc.id),
+        "created_by_ref": producer_id,
+        "created": timestamp(ioc.created_at),
+        "modified": timestamp(ioc.last_seen.max(ioc.created_at)),
+        "name": ioc.value,
+        "indicator_types": ["unknown"],
+        "pattern": pattern_for(&ioc.ioc_type, &ioc.value),
+        "pattern_type": "stix",
+        "valid_from": timestamp(ioc.first_seen),
+        "x_ioc_type": ioc.ioc_type,
+        "x_sighting_count": ioc.sighting_count,
+        "x_first_seen": timestamp(ioc.first_seen),
+        "x_last_seen": timestamp(ioc.last_seen),
+    })
+}
+
+pub fn pattern_for(ioc_type: &str, value: &str) -> String {
+    let path = match ioc_type {
+        "ipv4" => "ipv4-addr:value",
+        "ipv6" => "ipv6-addr:value",
+        "domain" | "onion" => "domain-name:value",
+        "url" | "telegram_invite" => "url:value",
+        "email" => "email-addr:value",
+        "md5" => "file:hashes.MD5",
+        "sha1" => "file:hashes.'SHA-1'",
+        "sha256" => "file:hashes.'SHA-256'",
+        // STIX 2.1 não tem objeto para carteiras de criptomoeda; usa um observável customizado
+        _ => "x-cryptocurrency-wallet:address",
+    };
+
+    format!("[{} = '{}']", path, value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn telegram_reference(external_id: i64, username: Option<&str>) -> Value {
+    let mut reference = json!({ "source_name": "telegram", "external_id": external_id.to_string() });
+    if let Some(username) = username {
+        reference["url"] = json!(format!("https://t.me/{}", username));
+    }
+    reference
+}
+
+pub fn timestamp(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
@@ -0,0 +1,279 @@
+use super::*;
+use crate::config::MispConfig;
+use crate::db::Ioc;
+use chrono::TimeZone;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn sample_data() -> ExportData {
+    let date = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    let group = TelegramGroup {
+        id: Uuid::new_v4(),
+        telegram_chat_id: 1234567890,
+        chat_type: "supergroup".to_string(),
+        title: Some("Mercado".to_string()),
+        username: None,
+        description: None,
+        invite_link: None,
+        member_count: None,
+        is_verified: false,
+        is_restricted: false,
+        is_scam: false,
+        is_fake: false,
+        collector_account_id: None,
+        created_at: date,
+        updated_at: date,
+    };
+    let user = TelegramUser {
+        id: Uuid::new_v4(),
+        telegram_user_id: 42,
+        username: Some("vendedor".to_string()),
+        first_name: Some("Fulano".to_string()),
+        last_name: None,
+        phone_number: None,
+        is_bot: false,
+        is_verified: false,
+        is_premium: false,
+        language_code: None,
+        created_at: date,
+        updated_at: date,
+    };
+    let message = |telegram_message_id: i64| TelegramMessage {
+        id: Uuid::new_v4(),
+        telegram_message_id,
+        user_id: Some(user.id),
+        group_id: Some(group.id),
+        message_text: Some("painel em evil.com e CVE-2024-1234".to_string()),
+        message_type: "text".to_string(),
+        date,
+        edit_date: None,
+        forward_from_user_id: None,
+        forward_from_group_id: None,
+        forward_date: None,
+        forward_from_message_id: None,
+        forward_sender_name: None,
+        reply_to_message_id: None,
+        media_file_id: None,
+        media_file_unique_id: None,
+        media_file_size: None,
+        media_mime_type: None,
+        media_file_name: None,
+        location_latitude: None,
+        location_longitude: None,
+        contact_phone_number: None,
+        contact_first_name: None,
+        contact_last_name: None,
+        deleted_at: None,
+        deletion_source: None,
+        language: Some("pt".to_string()),
+        collector_account_id: None,
+        created_at: date,
+    };
+    let ioc = |ioc_type: &str, value: &str| Ioc {
+        id: Uuid::new_v4(),
+        ioc_type: ioc_type.to_string(),
+        value: value.to_string(),
+        first_seen: date,
+        last_seen: date,
+        sighting_count: 2,
+        created_at: date,
+    };
+
+    let messages = vec![message(10), message(11)];
+    let domain = ioc("domain", "evil.com");
+    let cve = ioc("cve", "CVE-2024-1234");
+    let iocs = messages.iter().flat_map(|message| [
+        MessageIoc { message_id: message.id, ioc: domain.clone() },
+        MessageIoc { message_id: message.id, ioc: cve.clone() },
+    ]).collect();
+
+    ExportData { groups: vec![group], users: vec![user], messages, iocs }
+}
+
+fn objects_of<'a>(bundle: &'a Value, object_type: &str) -> Vec<&'a Value> {
+    bundle["objects"].as_array().unwrap().iter().filter(|object| object["type"] == object_type).collect()
+}
+
+#[test]
+fn parses_export_arguments() {
+    let options = ExportOptions::from_args(&args(&["stix", "--since", "2024-05-01", "--until", "2024-05-31", "--group", "@mercado", "-o", "out.json"])).unwrap();
+
+    assert_eq!(options.format, ExportFormat::Stix);
+    assert_eq!(options.filter.since, Some(Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap()));
+    assert_eq!(options.filter.until, Some(Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap()));
+    assert_eq!(options.filter.group.as_deref(), Some("@mercado"));
+    assert_eq!(options.output, Some(PathBuf::from("out.json")));
+}
+
+#[test]
+fn rejects_unknown_formats_and_flags() {
+    assert!(ExportOptions::from_args(&args(&["csv"])).is_err());
+    assert!(ExportOptions::from_args(&args(&["stix", "--verbose", "1"])).is_err());
+    assert!(ExportOptions::from_args(&args(&["stix", "--since"])).is_err());
+    assert!(ExportOptions::from_args(&args(&["stix", "--since", "ontem"])).is_err());
+    assert!(ExportOptions::from_args(&args(&["stix", "--per-day"])).is_err());
+}
+
+#[test]
+fn parses_misp_flags() {
+    let options = ExportOptions::from_args(&args(&["misp", "--per-day", "--group", "123", "--push"])).unwrap();
+
+    assert_eq!(options.format, ExportFormat::Misp);
+    assert!(options.per_day);
+    assert!(options.push);
+    assert_eq!(options.filter.group.as_deref(), Some("123"));
+}
+
+#[test]
+fn stix_bundle_maps_rows_to_sdos_without_duplicates() {
+    let data = sample_data();
+    let bundle = stix::build_bundle(&data);
+
+    assert_eq!(bundle["type"], "bundle");
+    assert!(bundle["objects"].as_array().unwrap().iter().all(|object| object["spec_version"] == "2.1"));
+    assert_eq!(objects_of(&bundle, "identity").len(), 2);
+    assert_eq!(objects_of(&bundle, "threat-actor").len(), 1);
+    assert_eq!(objects_of(&bundle, "note").len(), 2);
+    assert_eq!(objects_of(&bundle, "vulnerability").len(), 1);
+
+    let indicators = objects_of(&bundle, "indicator");
+    assert_eq!(indicators.len(), 1);
+    assert_eq!(indicators[0]["pattern"], "[domain-name:value = 'evil.com']");
+    assert_eq!(indicators[0]["pattern_type"], "stix");
+
+    // usuário→grupo, indicador→usuário e vulnerabilidade→usuário
+    assert_eq!(objects_of(&bundle, "relationship").len(), 3);
+
+    let note = objects_of(&bundle, "note")[0];
+    assert_eq!(note["object_refs"].as_array().unwrap().len(), 4);
+    assert_eq!(note["external_references"][0]["url"], "https://t.me/c/1234567890/10");
+}
+
+#[test]
+fn stix_ids_are_stable_across_exports() {
+    let data = sample_data();
+    let ids = |bundle: Value| bundle["objects"].as_array().unwrap().iter()
+        .map(|object| object["id"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(ids(stix::build_bundle(&data)), ids(stix::build_bundle(&data)));
+}
+
+#[test]
+fn stix_patterns_escape_quotes() {
+    assert_eq!(stix::pattern_for("url", "http://x.com/a'b"), r"[url:value = 'http://x.com/a\'b']");
+    assert_eq!(stix::pattern_for("sha256", "abc"), "[file:hashes.'SHA-256' = 'abc']");
+}
+
+#[test]
+fn misp_events_group_messages_by_chat_or_day() {
+    let mut data = sample_data();
+    data.messages[1].date += chrono::Duration::days(1);
+
+    let events = misp::build_events(&data, false);
+    assert_eq!(events.len(), 1);
+    let event = &events[0]["Event"];
+    assert_eq!(event["info"], "Telegram: Mercado");
+    assert_eq!(event["date"], "2024-05-01");
+    assert_eq!(event["uuid"], misp::event_uuid(&data.groups[0], None).to_string());
+
+    // O mesmo IOC em duas mensagens vira um único atributo
+    let attributes = event["Attribute"].as_array().unwrap();
+    assert_eq!(attributes.len(), 2);
+    let domain = attributes.iter().find(|attribute| attribute["type"] == "domain").unwrap();
+    assert_eq!(domain["value"], "evil.com");
+    assert_eq!(domain["to_ids"], true);
+    assert_eq!(domain["comment"], "painel em evil.com e CVE-2024-1234\n\nhttps://t.me/c/1234567890/10");
+    assert!(attributes.iter().any(|attribute| attribute["type"] == "vulnerability"));
+
+    let tags: Vec<&str> = event["Tag"].as_array().unwrap().iter().map(|tag| tag["name"].as_str().unwrap()).collect();
+    assert!(tags.contains(&"source:telegram"));
+    assert!(tags.contains(&"telegram:chat-id=\"1234567890\""));
+
+    let daily = misp::build_events(&data, true);
+    assert_eq!(daily.len(), 2);
+    assert_eq!(daily[1]["Event"]["info"], "Telegram: Mercado (2024-05-02)");
+    assert_ne!(daily[0]["Event"]["uuid"], daily[1]["Event"]["uuid"]);
+}
+
+#[test]
+fn misp_uuids_are_stable_across_exports() {
+    let data = sample_data();
+
+    assert_eq!(misp::build_events(&data, true), misp::build_events(&data, true));
+}
+
+#[test]
+fn misp_events_skip_chats_without_iocs() {
+    let mut data = sample_data();
+    data.iocs.clear();
+
+    assert!(misp::build_events(&data, false).is_empty());
+}
+
+// Servidor HTTP mínimo: responde com os status informados e repassa "MÉTODO caminho" de cada requisição
+async fn misp_stand_in(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        for status in statuses {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).await.unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).await.unwrap();
+            let request = request_line.split_whitespace().take(2).collect::<Vec<_>>().join(" ");
+            sender.send(request).ok();
+
+            let response = format!("HTTP/1.1 {} X\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}", status);
+            reader.into_inner().write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    (format!("http://{}/", address), receiver)
+}
+
+#[tokio::test]
+async fn misp_push_creates_then_updates_the_same_event() {
+    let event = misp::build_events(&sample_data(), false).remove(0);
+    let uuid = event["Event"]["uuid"].as_str().unwrap().to_string();
+    let (url, mut requests) = misp_stand_in(vec![404, 200, 200, 200]).await;
+    let client = misp::MispClient::new(&MispConfig { url, api_key: "chave".to_string(), verify_tls: true }).unwrap();
+
+    assert_eq!(client.push(&event).await.unwrap(), misp::PushOutcome::Created);
+    assert_eq!(requests.recv().await.unwrap(), format!("GET /events/view/{}", uuid));
+    assert_eq!(requests.recv().await.unwrap(), "POST /events/add");
+
+    assert_eq!(client.push(&event).await.unwrap(), misp::PushOutcome::Updated);
+    assert_eq!(requests.recv().await.unwrap(), format!("GET /events/view/{}", uuid));
+    assert_eq!(requests.recv().await.unwrap(), format!("POST /events/edit/{}", uuid));
+}
+
+#[tokio::test]
+async fn misp_push_reports_server_errors() {
+    let event = misp::build_events(&sample_data(), false).remove(0);
+    let (url, _requests) = misp_stand_in(vec![404, 500]).await;
+    let client = misp::MispClient::new(&MispConfig { url, api_key: "chave".to_string(), verify_tls: true }).unwrap();
+
+    assert!(client.push(&event).await.is_err());
+}
@@ -0,0 +1,53 @@
+use crate::telegram::ConnectionHealth;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::{json, Map};
+use std::sync::Arc;
+use tracing::info;
+
+type Accounts = Arc<Vec<(String, ConnectionHealth)>>;
+
+pub fn router(health: ConnectionHealth) -> Router {
+    accounts_router(vec![(crate::config::DEFAULT_ACCOUNT_ID.to_string(), health)])
+}
+
+pub fn accounts_router(accounts: Vec<(String, ConnectionHealth)>) -> Router {
+    Router::new()
+        .route("/health", get(health_check))
+        .with_state(Arc::new(accounts))
+}
+
+// Sem autenticação: expõe apenas o estado da conexão, para orquestradores e balanceadores
+pub async fn serve(bind: String, accounts: Vec<(String, ConnectionHealth)>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let listener = tokio::net::TcpListener::bind(&bind).await?;
+    info!("🩺 Health check ouvindo em http://{}/health", listener.local_addr()?);
+
+    axum::serve(listener, accounts_router(accounts)).await?;
+    Ok(())
+}
+
+// O estado no topo é o da primeira conta com problema; "accounts" traz o de cada uma
+async fn health_check(State(accounts): State<Accounts>) -> Response {
+    let states: Vec<_> = accounts.iter().map(|(id, health)| (id, health.state())).collect();
+    let healthy = states.iter().all(|(_, state)| state.is_healthy());
+    let status = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    let mut per_account = Map::new();
+    for (id, state) in &states {
+        let mut entry = json!(state);
+        entry["healthy"] = json!(state.is_healthy());
+        per_account.insert(id.to_string(), entry);
+    }
+
+    let worst = states.iter().find(|(_, state)| !state.is_healthy()).or(states.first());
+    let mut body = worst.map(|(_, state)| json!(state)).unwrap_or_else(|| json!({}));
+    body["healthy"] = json!(healthy);
+    body["accounts"] = per_account.into();
+    (status, Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,57 @@
+use super::*;
+use crate::telegram::ConnectionState;
+use crate::test_support::serve;
+use serde_json::Value;
+
+async fn spawn_server(health: ConnectionHealth) -> String {
+    format!("http://{}/health", serve(router(health)).await)
+}
+
+async fn get(url: &str) -> (u16, Value) {
+    let response = reqwest::get(url).await.unwrap();
+    (response.status().as_u16(), response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn reports_connection_state_with_status_code() {
+    let health = ConnectionHealth::new();
+    let url = spawn_server(health.clone()).await;
+
+    let (status, body) = get(&url).await;
+    assert_eq!(status, 503);
+    assert_eq!(body["state"], "connecting");
+    assert_eq!(body["healthy"], false);
+
+    health.set(ConnectionState::Connected);
+    let (status, body) = get(&url).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["healthy"], true);
+
+    health.set(ConnectionState::Unauthorized { reason: "AUTH_KEY_UNREGISTERED".to_string() });
+    let (status, body) = get(&url).await;
+    assert_eq!(status, 503);
+    assert_eq!(body["state"], "unauthorized");
+    assert_eq!(body["reason"], "AUTH_KEY_UNREGISTERED");
+}
+
+#[tokio::test]
+async fn any_unhealthy_account_fails_the_check() {
+    let principal = ConnectionHealth::new();
+    let burner = ConnectionHealth::new();
+    principal.set(ConnectionState::Connected);
+    burner.set(ConnectionState::Connected);
+
+    let router = accounts_router(vec![("principal".to_string(), principal), ("burner1".to_string(), burner.clone())]);
+    let url = format!("http://{}/health", serve(router).await);
+
+    let (status, body) = get(&url).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["accounts"]["burner1"]["state"], "connected");
+
+    burner.set(ConnectionState::Unauthorized { reason: "SESSION_REVOKED".to_string() });
+    let (status, body) = get(&url).await;
+    assert_eq!(status, 503);
+    assert_eq!(body["state"], "unauthorized");
+    assert_eq!(body["accounts"]["principal"]["healthy"], true);
+    assert_eq!(body["accounts"]["burner1"]["healthy"], false);
+}
@@ -0,0 +1,224 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::net::Ipv6Addr;
+use std::sync::LazyLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IocType {
+    Ipv4,
+    Ipv6,
+    Domain,
+    Url,
+    Email,
+    Md5,
+    Sha1,
+    Sha256,
+    Cve,
+    BitcoinWallet,
+    MoneroWallet,
+    EthereumWallet,
+    Onion,
+    TelegramInvite,
+}
+
+impl IocType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IocType::Ipv4 => "ipv4",
+            IocType::Ipv6 => "ipv6",
+            IocType::Domain => "domain",
+            IocType::Url => "url",
+            IocType::Email => "email",
+            IocType::Md5 => "md5",
+            IocType::Sha1 => "sha1",
+            IocType::Sha256 => "sha256",
+            IocType::Cve => "cve",
+            IocType::BitcoinWallet => "bitcoin_wallet",
+            IocType::MoneroWallet => "monero_wallet",
+            IocType::EthereumWallet => "ethereum_wallet",
+            IocType::Onion => "onion",
+            IocType::TelegramInvite => "telegram_invite",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExtractedIoc {
+    pub ioc_type: IocType,
+    pub value: String,
+}
+
+static REFANG_PATTERNS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| vec![
+    (Regex::new(r"(?i)\bhxxp(s?)").unwrap(), "http$1"),
+    (Regex::new(r"(?i)\bfxp\b").unwrap(), "ftp"),
+    (Regex::new(r"(?i)\[(?:\.|dot)\]|\((?:\.|dot)\)|\{(?:\.|dot)\}").unwrap(), "."),
+    (Regex::new(r"(?i)\[(?:@|at)\]|\((?:@|at)\)|\{(?:@|at)\}").unwrap(), "@"),
+    (Regex::new(r"\[://\]").unwrap(), "://"),
+    (Regex::new(r"\[:\]").unwrap(), ":"),
+    (Regex::new(r"\[/\]").unwrap(), "/"),
+]);
+
+static URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?i)\b(?:https?|ftp)://[^\s<>"'`]+"#).unwrap());
+static TELEGRAM_INVITE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:https?://)?(?:t\.me|telegram\.me|telegram\.dog)/(?:\+|joinchat/)[A-Za-z0-9_-]{8,}").unwrap()
+});
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b[a-z0-9._%+-]+@(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z]{2,63}\b").unwrap()
+});
+static ONION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b(?:[a-z2-7]{56}|[a-z2-7]{16})\.onion\b").unwrap());
+static DOMAIN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z][a-z0-9-]{0,61}[a-z0-9]\b").unwrap()
+});
+static IPV4: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\b").unwrap()
+});
+static IPV6_CANDIDATE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)[0-9a-f]{0,4}(?::[0-9a-f]{0,4}){2,7}").unwrap());
+static MD5: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b[a-f0-9]{32}\b").unwrap());
+static SHA1: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b[a-f0-9]{40}\b").unwrap());
+static SHA256: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b[a-f0-9]{64}\b").unwrap());
+static CVE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\bCVE-\d{4}-\d{4,7}\b").unwrap());
+static BITCOIN_LEGACY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[13][1-9A-HJ-NP-Za-km-z]{25,34}\b").unwrap());
+static BITCOIN_BECH32: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\bbc1[ac-hj-np-z02-9]{39,59}\b").unwrap());
+static ETHEREUM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b0x[a-fA-F0-9]{40}\b").unwrap());
+static MONERO: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[48][0-9AB][1-9A-HJ-NP-Za-km-z]{93}\b").unwrap());
+
+// Terminações que quase sempre são nomes de arquivo, não domínios
+const FILE_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "bin", "bat", "cmd", "ps1", "sh", "py", "js", "php", "html", "htm", "txt", "log", "csv",
+    "json", "xml", "yml", "yaml", "ini", "cfg", "conf", "sql", "db", "zip", "rar", "7z", "tar", "gz",
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "jpg", "jpeg", "png", "gif", "mp4", "mp3", "apk",
+    "iso", "img", "lnk", "jar", "msi", "onion",
+];
+
+const URL_TRAILING: &[char] = &['.', ',', ';', ':', '!', '?', ')', ']', '}', '>', '\'', '"'];
+
+pub fn refang(text: &str) -> String {
+    REFANG_PATTERNS.iter().fold(text.to_string(), |text, (pattern, replacement)| {
+        pattern.replace_all(&text, *replacement).into_owned()
+    })
+}
+
+pub fn extract_iocs(text: &str) -> Vec<ExtractedIoc> {
+    let text = refang(text);
+    let mut found = Vec::new();
+    let mut seen = HashSet::new();
+    let mut push = |ioc_type: IocType, value: String| {
+        let ioc = ExtractedIoc { ioc_type, value };
+        if seen.insert(ioc.clone()) {
+            found.push(ioc);
+        }
+    };
+
+    for url in URL.find_iter(&text) {
+        push(IocType::Url, url.as_str().trim_end_matches(URL_TRAILING).to_string());
+    }
+
+    for invite in TELEGRAM_INVITE.find_iter(&text) {
+        let invite = invite.as_str();
+        let invite = invite.split_once("://").map_or(invite, |(_, rest)| rest);
+        push(IocType::TelegramInvite, format!("https://{}", invite));
+    }
+
+    for email in EMAIL.find_iter(&text) {
+        push(IocType::Email, email.as_str().to_lowercase());
+    }
+
+    for onion in ONION.find_iter(&text) {
+        push(IocType::Onion, onion.as_str().to_lowercase());
+    }
+
+    for domain in DOMAIN.find_iter(&text) {
+        let domain = domain.as_str().to_lowercase();
+        let tld = domain.rsplit('.').next().unwrap_or_default();
+        let is_ip = IPV4.is_match(&domain);
+        if !is_ip && !tld.chars().any(|c| c.is_ascii_digit()) && !FILE_EXTENSIONS.contains(&tld) {
+            push(IocType::Domain, domain);
+        }
+    }
+
+    for ip in IPV4.find_iter(&text) {
+        push(IocType::Ipv4, ip.as_str().to_string());
+    }
+
+    // Sem lookaround no regex: confere as bordas e exige ao menos dois grupos para não pegar "std::net" ou horários
+    for candidate in IPV6_CANDIDATE.find_iter(&text) {
+        let bounded = !text[..candidate.start()].ends_with(|c: char| c.is_alphanumeric() || c == ':' || c == '.')
+            && !text[candidate.end()..].starts_with(|c: char| c.is_alphanumeric() || c == ':' || c == '.');
+        let groups = candidate.as_str().split(':').filter(|group| !group.is_empty()).count();
+        if !bounded || groups < 2 {
+            continue;
+        }
+        if let Ok(ip) = candidate.as_str().parse::<Ipv6Addr>() {
+            push(IocType::Ipv6, ip.to_string());
+        }
+    }
+
+    for (pattern, ioc_type) in [(&*MD5, IocType::Md5), (&*SHA1, IocType::Sha1), (&*SHA256, IocType::Sha256)] {
+        for hash in pattern.find_iter(&text) {
+            push(ioc_type, hash.as_str().to_lowercase());
+        }
+    }
+
+    for cve in CVE.find_iter(&text) {
+        push(IocType::Cve, cve.as_str().to_uppercase());
+    }
+
+    for wallet in BITCOIN_LEGACY.find_iter(&text) {
+        if base58check_valid(wallet.as_str()) {
+            push(IocType::BitcoinWallet, wallet.as_str().to_string());
+        }
+    }
+
+    for wallet in BITCOIN_BECH32.find_iter(&text) {
+        push(IocType::BitcoinWallet, wallet.as_str().to_lowercase());
+    }
+
+    for wallet in ETHEREUM.find_iter(&text) {
+        push(IocType::EthereumWallet, wallet.as_str().to_lowercase());
+    }
+
+    for wallet in MONERO.find_iter(&text) {
+        push(IocType::MoneroWallet, wallet.as_str().to_string());
+    }
+
+    found
+}
+
+// Endereços legados de Bitcoin carregam checksum (SHA-256 duplo) que elimina falsos positivos
+fn base58check_valid(value: &str) -> bool {
+    const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in value.bytes() {
+        let Some(mut carry) = ALPHABET.iter().position(|a| *a == c).map(|d| d as u32) else {
+            return false;
+        };
+        for byte in bytes.iter_mut().rev() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, (carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+
+    let leading_zeros = value.bytes().take_while(|c| *c == b'1').count();
+    let mut decoded = vec![0u8; leading_zeros];
+    decoded.extend(bytes);
+
+    if decoded.len() != 25 {
+        return false;
+    }
+
+    let (payload, checksum) = decoded.split_at(21);
+    let hash = Sha256::digest(Sha256::digest(payload));
+    &hash[..4] == checksum
+}
+
+#[cfg(test)]
+mod tests;
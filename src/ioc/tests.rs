@@ -0,0 +1,94 @@
+use super::*;
+
+fn values_of(iocs: &[ExtractedIoc], ioc_type: IocType) -> Vec<&str> {
+    iocs.iter().filter(|ioc| ioc.ioc_type == ioc_type).map(|ioc| ioc.value.as_str()).collect()
+}
+
+#[test]
+fn refang_restores_defanged_indicators() {
+    assert_eq!(refang("hxxps://evil[.]com/path"), "https://evil.com/path");
+    assert_eq!(refang("admin[@]evil(.)org"), "admin@evil.org");
+    assert_eq!(refang("hxxp[://]10[.]0[.]0[.]1"), "http://10.0.0.1");
+    assert_eq!(refang("evil[dot]net"), "evil.net");
+}
+
+#[test]
+fn extracts_network_indicators_from_defanged_text() {
+    let iocs = extract_iocs("C2 em hxxps://evil[.]com/gate.php, backup 185.220.101[.]4 e contato ops[@]evil[.]com.");
+
+    assert_eq!(values_of(&iocs, IocType::Url), vec!["https://evil.com/gate.php"]);
+    assert_eq!(values_of(&iocs, IocType::Ipv4), vec!["185.220.101.4"]);
+    assert_eq!(values_of(&iocs, IocType::Email), vec!["ops@evil.com"]);
+    assert_eq!(values_of(&iocs, IocType::Domain), vec!["evil.com"]);
+}
+
+#[test]
+fn ignores_file_names_that_look_like_domains() {
+    let iocs = extract_iocs("baixe o payload.exe e o leia-me.txt");
+    assert!(values_of(&iocs, IocType::Domain).is_empty());
+}
+
+#[test]
+fn extracts_ipv6_without_matching_paths_or_times() {
+    let iocs = extract_iocs("servidor 2001:db8::ff00:42:8329 às 12:30:45 usando std::net");
+    assert_eq!(values_of(&iocs, IocType::Ipv6), vec!["2001:db8::ff00:42:8329"]);
+}
+
+#[test]
+fn extracts_hashes_by_length() {
+    let text = "md5 900150983CD24FB0D6963F7D28E17F72 sha1 a9993e364706816aba3e25717850c26c9cd0d89d \
+                sha256 ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    let iocs = extract_iocs(text);
+
+    assert_eq!(values_of(&iocs, IocType::Md5), vec!["900150983cd24fb0d6963f7d28e17f72"]);
+    assert_eq!(values_of(&iocs, IocType::Sha1), vec!["a9993e364706816aba3e25717850c26c9cd0d89d"]);
+    assert_eq!(values_of(&iocs, IocType::Sha256), vec!["ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"]);
+}
+
+#[test]
+fn extracts_cves_normalized_to_uppercase() {
+    let iocs = extract_iocs("explorando cve-2021-44228 e CVE-2023-12345");
+    assert_eq!(values_of(&iocs, IocType::Cve), vec!["CVE-2021-44228", "CVE-2023-12345"]);
+}
+
+#[test]
+fn extracts_wallets_and_validates_bitcoin_checksum() {
+    let text = "btc 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa falso 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb \
+                bech32 bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq \
+                eth 0xde0B295669a9FD93d5F28D9Ec85E40f4cb697BAe \
+                xmr 44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A";
+    let iocs = extract_iocs(text);
+
+    assert_eq!(values_of(&iocs, IocType::BitcoinWallet), vec![
+        "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+        "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+    ]);
+    assert_eq!(values_of(&iocs, IocType::EthereumWallet), vec!["0xde0b295669a9fd93d5f28d9ec85e40f4cb697bae"]);
+    assert_eq!(values_of(&iocs, IocType::MoneroWallet), vec![
+        "44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A",
+    ]);
+}
+
+#[test]
+fn extracts_onion_and_telegram_invites() {
+    let text = "mirror duskgytldkxiuqc6.onion e \
+                juhanurmihxlp77nkq76byazcldy2hlmovfu2epvl5ankdibsot4csyd[.]onion, \
+                grupo t.me/+AbCdEfGhIjKl e https://telegram.me/joinchat/XyZ12345678";
+    let iocs = extract_iocs(text);
+
+    assert_eq!(values_of(&iocs, IocType::Onion), vec![
+        "duskgytldkxiuqc6.onion",
+        "juhanurmihxlp77nkq76byazcldy2hlmovfu2epvl5ankdibsot4csyd.onion",
+    ]);
+    assert_eq!(values_of(&iocs, IocType::TelegramInvite), vec![
+        "https://t.me/+AbCdEfGhIjKl",
+        "https://telegram.me/joinchat/XyZ12345678",
+    ]);
+    assert!(!values_of(&iocs, IocType::Domain).iter().any(|domain| domain.ends_with(".onion")));
+}
+
+#[test]
+fn deduplicates_repeated_indicators() {
+    let iocs = extract_iocs("8.8.8.8 8.8.8.8 8[.]8[.]8[.]8");
+    assert_eq!(values_of(&iocs, IocType::Ipv4), vec!["8.8.8.8"]);
+}
@@ -0,0 +1,85 @@
+use whatlang::Lang;
+
+// Configuração de busca textual usada quando o idioma não é detectado ou o Postgres não tem uma própria
+pub const FALLBACK_SEARCH_CONFIG: &str = "simple";
+
+// Abaixo disso o detector erra demais (saudações, emojis, um link solto)
+const MIN_DETECTABLE_CHARS: usize = 20;
+
+// is_reliable do whatlang descarta quase todo texto curto; a confiança é a distância para o segundo colocado
+const MIN_CONFIDENCE: f64 = 0.1;
+
+// Idioma (ISO 639-1) e configuração de busca do Postgres; só configurações presentes desde o Postgres 13
+const LANGUAGES: &[(Lang, &str, &str)] = &[
+    (Lang::Ara, "ar", "arabic"),
+    (Lang::Dan, "da", "danish"),
+    (Lang::Deu, "de", "german"),
+    (Lang::Ell, "el", "greek"),
+    (Lang::Eng, "en", "english"),
+    (Lang::Fin, "fi", "finnish"),
+    (Lang::Fra, "fr", "french"),
+    (Lang::Hun, "hu", "hungarian"),
+    (Lang::Ind, "id", "indonesian"),
+    (Lang::Ita, "it", "italian"),
+    (Lang::Lit, "lt", "lithuanian"),
+    (Lang::Nep, "ne", "nepali"),
+    (Lang::Nld, "nl", "dutch"),
+    (Lang::Nob, "no", "norwegian"),
+    (Lang::Por, "pt", "portuguese"),
+    (Lang::Ron, "ro", "romanian"),
+    (Lang::Rus, "ru", "russian"),
+    (Lang::Spa, "es", "spanish"),
+    (Lang::Srp, "sr", "serbian"),
+    (Lang::Swe, "sv", "swedish"),
+    (Lang::Tam, "ta", "tamil"),
+    (Lang::Tur, "tr", "turkish"),
+    // Sem stemmer no Postgres: indexados com simple
+    (Lang::Cmn, "zh", FALLBACK_SEARCH_CONFIG),
+    (Lang::Jpn, "ja", FALLBACK_SEARCH_CONFIG),
+    (Lang::Kor, "ko", FALLBACK_SEARCH_CONFIG),
+    (Lang::Ukr, "uk", FALLBACK_SEARCH_CONFIG),
+    (Lang::Pol, "pl", FALLBACK_SEARCH_CONFIG),
+    (Lang::Ces, "cs", FALLBACK_SEARCH_CONFIG),
+    (Lang::Heb, "he", FALLBACK_SEARCH_CONFIG),
+    (Lang::Pes, "fa", FALLBACK_SEARCH_CONFIG),
+    (Lang::Vie, "vi", FALLBACK_SEARCH_CONFIG),
+    (Lang::Tha, "th", FALLBACK_SEARCH_CONFIG),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedLanguage {
+    pub code: String,
+    pub search_config: &'static str,
+}
+
+pub fn detect(text: &str) -> Option<DetectedLanguage> {
+    // URLs, menções e hashtags não dizem nada sobre o idioma e confundem o detector
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|word| !word.contains("://") && !word.starts_with('@') && !word.starts_with('#'))
+        .collect();
+    let cleaned = words.join(" ");
+    if cleaned.chars().filter(|c| c.is_alphabetic()).count() < MIN_DETECTABLE_CHARS && !is_cjk(&cleaned) {
+        return None;
+    }
+
+    let info = whatlang::detect(&cleaned).filter(|info| info.confidence() >= MIN_CONFIDENCE)?;
+    let lang = info.lang();
+
+    Some(match LANGUAGES.iter().find(|(known, _, _)| *known == lang) {
+        Some((_, code, search_config)) => DetectedLanguage { code: code.to_string(), search_config },
+        None => DetectedLanguage { code: lang.code().to_string(), search_config: FALLBACK_SEARCH_CONFIG },
+    })
+}
+
+pub fn search_config(language: Option<&DetectedLanguage>) -> &'static str {
+    language.map_or(FALLBACK_SEARCH_CONFIG, |language| language.search_config)
+}
+
+// Um ideograma carrega uma palavra: poucos caracteres já bastam para detectar chinês ou japonês
+fn is_cjk(text: &str) -> bool {
+    text.chars().filter(|c| matches!(*c as u32, 0x3040..=0x30FF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF)).count() >= 4
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,25 @@
+use super::*;
+
+fn detected(text: &str) -> Option<(String, &'static str)> {
+    detect(text).map(|language| (language.code, language.search_config))
+}
+
+#[test]
+fn detects_the_languages_we_monitor() {
+    assert_eq!(detected("Vendo acesso a painéis de bancos, entrego hoje mesmo com garantia total"), Some(("pt".to_string(), "portuguese")));
+    assert_eq!(detected("Selling fresh bank logs with full access, delivery within the hour guaranteed"), Some(("en".to_string(), "english")));
+    assert_eq!(detected("Продаю свежие логи банков с полным доступом, доставка в течение часа"), Some(("ru".to_string(), "russian")));
+    assert_eq!(detected("Vendo registros bancarios frescos con acceso completo, entrega en una hora"), Some(("es".to_string(), "spanish")));
+}
+
+#[test]
+fn chinese_falls_back_to_simple_search() {
+    assert_eq!(detected("出售新鲜的银行日志，完全访问，一小时内交付"), Some(("zh".to_string(), "simple")));
+}
+
+#[test]
+fn short_or_noisy_text_is_not_detected() {
+    assert_eq!(detect("ok 👍"), None);
+    assert_eq!(detect("https://example.com/very/long/path/segment @someone #tag"), None);
+    assert_eq!(search_config(None), FALLBACK_SEARCH_CONFIG);
+}
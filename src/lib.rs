@@ -0,0 +1,18 @@
+pub mod api;
+pub mod cli;
+pub mod config;
+pub mod db;
+pub mod export;
+pub mod health;
+pub mod ioc;
+pub mod language;
+pub mod media;
+pub mod notify;
+pub mod policy;
+pub mod shutdown;
+pub mod taxii;
+pub mod telegram;
+pub mod watchlist;
+
+#[cfg(test)]
+pub mod test_support;
@@ -1,65 +1,357 @@
-mod config;
-mod db;
-mod telegram;
-
-use tracing::{info, warn, Level};
+use tracing::{info, warn};
 use tracing_subscriber::FmtSubscriber;
-use config::Config;
-use db::Database;
+use f1000::cli::{self, Cli, CliError, Command, Exit, SearchOptions};
+use f1000::config::{AccountConfig, Config};
+use f1000::db::{CollectionStats, Database, MessageHit, MigrationOutcome, NewWatchlistRule, TextSearch, WatchlistRule};
+use f1000::export;
+use f1000::media::MediaDownloader;
+use f1000::notify::{NotificationDispatcher, TelegramNotifier};
+use f1000::policy::{PolicyCache, PolicySet};
+use f1000::shutdown::{Shutdown, DRAIN_TIMEOUT};
+use grammers_client::Client;
+use grammers_session::Session;
+use std::process::ExitCode;
+use std::sync::Arc;
+use f1000::telegram::{Backfill, ChatClaims, MessageProcessor, TelegramClient};
+
+type Error = Box<dyn std::error::Error>;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let _subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cli = match Cli::parse(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("f1000: {}\nUse `f1000 help` para ver os comandos.", e);
+            return ExitCode::from(e.exit.code());
+        },
+    };
+
+    match &cli.command {
+        Command::Help(topic) => {
+            print!("{}", topic.as_deref().and_then(cli::command_help).unwrap_or(cli::USAGE));
+            return ExitCode::SUCCESS;
+        },
+        Command::Version => {
+            println!("f1000 {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        },
+        _ => {},
+    }
+
+    // Logs vão para stderr para não misturar com exportações escritas em stdout
+    FmtSubscriber::builder()
+        .with_max_level(cli.log_level)
+        .with_writer(std::io::stderr)
         .init();
 
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("f1000: {}", e);
+            ExitCode::from(cli::exit_for(e.as_ref()).code())
+        },
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Error> {
     info!("F1000 - Sistema de Threat Intel iniciando...");
 
-                   let config = Config::load()?;
-    
-
-    let database = Database::new(&config.database.url).await?;
-    
-    if config.is_telegram_configured() {
-        info!("Credenciais do Telegram configuradas");
-        
-        let client = crate::telegram::TelegramClient::new(
-            config.telegram.api_id,
-            config.telegram.api_hash,
-            config.telegram.phone_number,
-            config.telegram.session_path,
-        );
-        
-        info!("Cliente Telegram criado com sucesso!");
-        
-        match client.connect().await {
-            Ok(mut telegram_client) => {
-                info!("Conectado ao Telegram!");
-                
-                match client.sign_in(&mut telegram_client).await {
-                    Ok(_) => {
-                        info!("✅ Login realizado com sucesso!");
-                        info!("🎉 Sistema F1000 conectado ao Telegram!");
-                        
-                        if let Err(e) = client.save_session(&telegram_client).await {
-                            warn!("⚠️ Erro ao salvar sessão: {}", e);
-                        }
-                        
-                        match client.start_listening(&mut telegram_client, &database).await {
-                            Ok(_) => info!("📱 Teste de coleta concluído"),
-                            Err(e) => warn!("❌ Erro na coleta: {}", e),
+    let config = Config::load_from(cli.config_path.as_deref())
+        .map_err(|e| CliError::new(Exit::Config, e.to_string()))?;
+
+    let accounts = match &cli.account {
+        Some(id) => vec![config.account(id)
+            .ok_or_else(|| CliError::usage(format!("conta {} não está em TELEGRAM_ACCOUNTS", id)))?],
+        None => config.accounts.iter().collect(),
+    };
+
+    // A sessão é um arquivo local: dá para inspecioná-la sem banco nem rede
+    match cli.command {
+        Command::SessionInfo => return session_info(accounts[0]),
+        Command::ConfigCheck => return config_check(&config),
+        _ => {},
+    }
+
+    let database = Database::new(&config.database.url).await
+        .map_err(|e| CliError::new(Exit::Unavailable, format!("falha ao conectar no banco de dados: {}", e)))?;
+
+    match cli.command {
+        Command::Migrate => migrate(&database).await,
+        Command::Export(options) => export::run(&database, &options, config.misp.as_ref()).await,
+        Command::Search(options) => search(&database, &options).await,
+        Command::Stats => stats(&database).await,
+        Command::Taxii => f1000::taxii::serve(database, config.taxii.clone()).await,
+        Command::Api => f1000::api::serve(database, config.api.clone()).await,
+        Command::Backfill => collect(&config, &accounts, database, true).await,
+        Command::Listen => collect(&config, &accounts, database, false).await,
+        // Comandos interativos agem numa conta só: a de --account ou a primeira
+        Command::Login => {
+            let (client, mut telegram_client) = connect(&config, accounts[0]).await?;
+            client.sign_in(&mut telegram_client).await?;
+            client.save_session(&telegram_client).await
+        },
+        Command::Dialogs => {
+            let (client, mut telegram_client) = connect(&config, accounts[0]).await?;
+            authorize(&client, &mut telegram_client).await?;
+            dialogs(&telegram_client).await
+        },
+        Command::Join(target) => {
+            let (client, mut telegram_client) = connect(&config, accounts[0]).await?;
+            authorize(&client, &mut telegram_client).await?;
+            let chat = f1000::telegram::join_chat(&telegram_client, &target).await?;
+            println!("{}\t{}\t{}", chat.id(), f1000::telegram::chat_type(&chat), chat.name());
+            client.save_session(&telegram_client).await
+        },
+        Command::SessionInfo | Command::ConfigCheck | Command::Help(_) | Command::Version => Ok(()),
+    }
+}
+
+fn telegram_client(config: &Config, account: &AccountConfig) -> Result<TelegramClient, Error> {
+    if !account.is_configured() {
+        return Err(CliError::new(Exit::Config, format!("credenciais do Telegram da conta {} não configuradas: defina TELEGRAM_API_ID, TELEGRAM_API_HASH e TELEGRAM_PHONE_NUMBER (veja env.example)", account.id)).into());
+    }
+
+    Ok(TelegramClient::new(
+        account.api_id,
+        account.api_hash.clone(),
+        account.phone_number.clone(),
+        account.session_path.clone(),
+    )
+    .with_reconnect_delays(
+        std::time::Duration::from_millis(config.telegram.reconnect_base_delay_ms),
+        std::time::Duration::from_millis(config.telegram.reconnect_max_delay_ms),
+    )
+    .with_login(
+        config.telegram.login_method,
+        f1000::telegram::login::provider_for(&config.telegram.credentials),
+        config.telegram.login_attempts,
+    ))
+}
+
+// Sem provedor de credenciais utilizável, sign_in falha em vez de travar esperando stdin
+async fn authorize(client: &TelegramClient, telegram_client: &mut Client) -> Result<(), Error> {
+    if telegram_client.is_authorized().await? {
+        return Ok(());
+    }
+
+    client.sign_in(telegram_client).await?;
+    client.save_session(telegram_client).await
+}
+
+async fn connect(config: &Config, account: &AccountConfig) -> Result<(TelegramClient, Client), Error> {
+    let client = telegram_client(config, account)?;
+    let telegram_client = client.connect().await
+        .map_err(|e| CliError::new(Exit::Unavailable, format!("falha ao conectar ao Telegram (conta {}): {}", account.id, e)))?;
+    info!("Conectado ao Telegram! (conta {})", account.id);
+    Ok((client, telegram_client))
+}
+
+async fn collect(config: &Config, accounts: &[&AccountConfig], database: Database, backfill: bool) -> Result<(), Error> {
+    // Login de uma conta por vez: com stdin, os pedidos de código não se misturam
+    let mut collectors = Vec::new();
+    for account in accounts {
+        let (client, mut telegram_client) = connect(config, account).await?;
+        authorize(&client, &mut telegram_client).await?;
+        collectors.push((*account, client, telegram_client));
+    }
+    info!("🎉 Sistema F1000 conectado ao Telegram com {} conta(s)!", collectors.len());
+
+    if !config.watchlists.is_empty() {
+        let rules: Vec<NewWatchlistRule> = config.watchlists.iter().map(|rule| NewWatchlistRule {
+            name: rule.name.clone(),
+            pattern: rule.pattern.clone(),
+            match_type: rule.match_type.as_str().to_string(),
+            max_distance: rule.max_distance,
+        }).collect();
+        let created = WatchlistRule::sync(database.get_pool(), &rules).await?;
+        info!("📋 {} regra(s) de watchlist da configuração sincronizadas ({} nova(s))", rules.len(), created);
+    }
+
+    let policies = PolicySet::new(config.policy.default_mode, config.policy.chats.clone());
+    info!("🛂 Política de captura: {} por padrão, {} regra(s) por chat na configuração",
+        config.policy.default_mode.as_str(), config.policy.chats.len());
+    let mut processor = MessageProcessor::new(database.clone())
+        .with_policies(PolicyCache::new(database.clone(), policies));
+    if config.media.enabled {
+        info!("📎 Download de mídias habilitado em: {}", config.media.storage_path);
+        processor = processor.with_media_downloader(MediaDownloader::new(database.clone(), config.media.clone()));
+    }
+
+    let mut notification_dispatcher = NotificationDispatcher::new(database.clone(), &config.notify)
+        .map_err(|e| CliError::new(Exit::Config, e.to_string()))?;
+    if let Some(target) = &config.notify.telegram_chat {
+        // Alertas saem pela primeira conta
+        let telegram_notifier = TelegramNotifier::resolve(collectors[0].2.clone(), target).await
+            .map_err(|e| e as Error)?;
+        info!("📣 Alertas também serão publicados no chat {}", telegram_notifier.chat_id());
+        processor = processor.with_ignored_chat(telegram_notifier.chat_id());
+        notification_dispatcher = notification_dispatcher.with_notifier(Arc::new(telegram_notifier));
+    }
+    if !notification_dispatcher.is_empty() {
+        info!("📨 Notificação de alertas habilitada");
+        processor = processor.with_notification_dispatcher(notification_dispatcher);
+    }
+
+    // Só a partir daqui Ctrl+C deixa de matar o processo: durante o login ele ainda interrompe na hora
+    let shutdown = Shutdown::new();
+    shutdown.listen_for_signals();
+
+    if let (false, Some(bind)) = (backfill, config.telegram.health_bind.clone()) {
+        let accounts = collectors.iter().map(|(account, client, _)| (account.id.clone(), client.health())).collect();
+        tokio::spawn(async move {
+            if let Err(e) = f1000::health::serve(bind, accounts).await {
+                warn!("❌ Erro no health check: {}", e);
+            }
+        });
+    }
+
+    // Todas as contas dividem as claims, inclusive as fora de --account: a atribuição de chats continua valendo
+    let claims = ChatClaims::new(&config.accounts);
+    let runs = collectors.iter_mut().map(|(account, client, telegram_client)| {
+        let processor = processor.clone().with_account(&account.id, claims.clone());
+        let (database, shutdown, claims) = (&database, &shutdown, &claims);
+        async move {
+            let result = if backfill {
+                Backfill::new(database.clone(), processor, config.backfill.clone())
+                    .with_shutdown(shutdown.clone())
+                    .run(telegram_client)
+                    .await
+            } else {
+                // Chats com política full também recebem o histórico, em segundo plano e retomando do cursor salvo
+                let history = Backfill::new(database.clone(), processor.clone(), config.backfill.clone())
+                    .full_only()
+                    .with_shutdown(shutdown.clone());
+                let history_client = telegram_client.clone();
+                let (listened, _) = tokio::join!(
+                    client.start_listening(telegram_client, database, &processor, shutdown),
+                    async {
+                        if let Err(e) = history.run(&history_client).await {
+                            warn!("❌ Erro no backfill da conta {}: {}", account.id, e);
                         }
                     },
-                    Err(e) => warn!("❌ Erro no login: {}", e),
-                }
-            },
-            Err(e) => warn!("Erro ao conectar: {}", e),
+                );
+                listened
+            };
+            // Uma conta revogada não derruba as outras: os chats dela passam às contas que seguem coletando
+            claims.release(&account.id);
+            if let Err(e) = &result {
+                warn!("❌ Coleta da conta {} interrompida: {}", account.id, e);
+            }
+            result
+        }
+    });
+    let results = futures::future::join_all(runs).await;
+
+    // Downloads de mídia ainda usam o cliente: ele só é liberado depois que as tarefas terminam
+    let abandoned = processor.tasks().drain(DRAIN_TIMEOUT).await;
+    for (account, client, telegram_client) in &collectors {
+        if let Err(e) = client.save_session(telegram_client).await {
+            warn!("⚠️ Erro ao salvar sessão da conta {}: {}", account.id, e);
         }
-    } else {
-        warn!("Credenciais do Telegram não configuradas");
-        warn!("Configure as variáveis de ambiente ou crie um arquivo .env");
-        info!("Exemplo de configuração em env.example");
+    }
+    database.close().await;
+
+    // Sessão revogada não se resolve sozinha: encerra com erro em vez de seguir parado
+    if let Some(error) = results.into_iter().find_map(Result::err) {
+        return Err(error);
+    }
+    if abandoned > 0 {
+        return Err(format!("{} tarefa(s) abortada(s) no encerramento", abandoned).into());
+    }
+    info!("👋 F1000 encerrado");
+    Ok(())
+}
+
+async fn dialogs(telegram_client: &Client) -> Result<(), Error> {
+    for chat in f1000::telegram::list_dialog_chats(telegram_client).await? {
+        let username = chat.username().map(|username| format!("@{}", username)).unwrap_or_else(|| "-".to_string());
+        println!("{}\t{}\t{}\t{}", chat.id(), f1000::telegram::chat_type(&chat), username, chat.name());
+    }
+    Ok(())
+}
+
+async fn migrate(database: &Database) -> Result<(), Error> {
+    match database.migrate().await? {
+        MigrationOutcome::Applied { from, to } => println!("schema migrado da versão {} para a {}", from, to),
+        MigrationOutcome::AlreadyApplied { version } => println!("schema já está na versão {}; nada a fazer", version),
+    }
+    Ok(())
+}
+
+async fn search(database: &Database, options: &SearchOptions) -> Result<(), Error> {
+    let group_id = match &options.group {
+        Some(group) => Some(export::resolve_group(database, group).await?.id),
+        None => None,
+    };
+
+    let hits = MessageHit::search(database.get_pool(), &TextSearch {
+        query: options.query.clone(),
+        group_id,
+        language: options.language.clone(),
+        limit: options.limit,
+        offset: 0,
+    }).await?;
+
+    for hit in &hits {
+        let snippet = hit.snippet.replace("<mark>", "«").replace("</mark>", "»").replace('\n', " ");
+        println!("{}\t{}\t{:.3}\t{}", hit.message.date.to_rfc3339(), hit.message.id, hit.rank, snippet);
+    }
+    info!("🔎 {} resultado(s)", hits.len());
+    Ok(())
+}
+
+async fn stats(database: &Database) -> Result<(), Error> {
+    let stats = CollectionStats::load(database.get_pool()).await?;
+    let date = |date: Option<chrono::DateTime<chrono::Utc>>| date.map(|date| date.to_rfc3339()).unwrap_or_else(|| "-".to_string());
+
+    println!("grupos\t{}", stats.groups);
+    println!("usuarios\t{}", stats.users);
+    println!("mensagens\t{}", stats.messages);
+    println!("mensagens_apagadas\t{}", stats.deleted_messages);
+    println!("midias\t{}", stats.media);
+    println!("iocs\t{}", stats.iocs);
+    println!("alertas\t{}", stats.alerts);
+    println!("primeira_mensagem\t{}", date(stats.first_message_date));
+    println!("ultima_mensagem\t{}", date(stats.last_message_date));
+    Ok(())
+}
+
+fn config_check(config: &Config) -> Result<(), Error> {
+    for (field, value) in config.describe() {
+        println!("{} = {}", field, value);
+    }
+    info!("✅ Configuração válida");
+    Ok(())
+}
+
+fn session_info(account: &AccountConfig) -> Result<(), Error> {
+    let path = std::path::Path::new(&account.session_path);
+    println!("conta\t{}", account.id);
+    println!("arquivo\t{}", path.display());
+
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => {
+            println!("existe\tnão");
+            return Err(CliError::new(Exit::NoPermission, "arquivo de sessão não encontrado: rode `f1000 login`").into());
+        },
+    };
+    println!("existe\tsim");
+    println!("tamanho\t{}", metadata.len());
+    if let Ok(modified) = metadata.modified() {
+        println!("modificado\t{}", chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339());
     }
 
+    let session = Session::load_file(path)
+        .map_err(|e| CliError::new(Exit::NoPermission, format!("arquivo de sessão ilegível: {}", e)))?;
+    let Some(user) = session.get_user() else {
+        println!("autorizada\tnão");
+        return Err(CliError::new(Exit::NoPermission, "sessão não autorizada: rode `f1000 login`").into());
+    };
+    println!("autorizada\tsim");
+    println!("usuario_id\t{}", user.id);
+    println!("datacenter\t{}", user.dc);
+    println!("bot\t{}", if user.bot { "sim" } else { "não" });
     Ok(())
 }
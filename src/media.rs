@@ -0,0 +1,211 @@
+use crate::config::MediaConfig;
+use crate::db::{Database, NewTelegramMedia, TelegramGroup, TelegramMedia, TelegramMessage};
+use grammers_client::types::Message;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+type MediaError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredFile {
+    pub sha256: String,
+    pub sha1: String,
+    pub md5: String,
+    pub size: i64,
+    pub path: PathBuf,
+}
+
+// Armazenamento endereçado por conteúdo: cada arquivo fica em <raiz>/<ab>/<cd>/<sha256>
+#[derive(Debug, Clone)]
+pub struct ContentStore {
+    root: PathBuf,
+}
+
+impl ContentStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path_for(&self, sha256: &str) -> PathBuf {
+        self.root.join(&sha256[0..2]).join(&sha256[2..4]).join(sha256)
+    }
+
+    pub fn temp_path(&self) -> PathBuf {
+        self.root.join(".tmp").join(Uuid::new_v4().to_string())
+    }
+
+    // Calcula os hashes do arquivo temporário e move para o caminho definitivo
+    pub async fn ingest(&self, temp_path: &Path) -> Result<StoredFile, MediaError> {
+        let mut file = tokio::fs::File::open(temp_path).await?;
+        let mut sha256 = Sha256::new();
+        let mut sha1 = Sha1::new();
+        let mut md5 = Md5::new();
+        let mut size = 0i64;
+        let mut buffer = vec![0u8; 64 * 1024];
+
+        loop {
+            let read = file.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            sha256.update(&buffer[..read]);
+            sha1.update(&buffer[..read]);
+            md5.update(&buffer[..read]);
+            size += read as i64;
+        }
+        drop(file);
+
+        let sha256 = hex::encode(sha256.finalize());
+        let path = self.path_for(&sha256);
+
+        if tokio::fs::try_exists(&path).await? {
+            tokio::fs::remove_file(temp_path).await?;
+        } else {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::rename(temp_path, &path).await?;
+        }
+
+        Ok(StoredFile {
+            sha256,
+            sha1: hex::encode(sha1.finalize()),
+            md5: hex::encode(md5.finalize()),
+            size,
+            path,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct MediaDownloader {
+    database: Database,
+    config: Arc<MediaConfig>,
+    store: ContentStore,
+    permits: Arc<Semaphore>,
+}
+
+impl MediaDownloader {
+    pub fn new(database: Database, config: MediaConfig) -> Self {
+        Self {
+            database,
+            store: ContentStore::new(&config.storage_path),
+            permits: Arc::new(Semaphore::new(config.max_concurrent_downloads.max(1))),
+            config: Arc::new(config),
+        }
+    }
+
+    pub fn should_download(&self, group: &TelegramGroup, saved: &TelegramMessage) -> bool {
+        if !self.config.enabled || saved.media_file_id.is_none() {
+            return false;
+        }
+
+        if !chat_enabled(&self.config.enabled_chats, group) {
+            return false;
+        }
+
+        if saved.media_file_size.is_some_and(|size| size > self.config.max_file_size) {
+            info!("📏 Mídia da mensagem {} ignorada: {} bytes acima do limite",
+                saved.telegram_message_id, saved.media_file_size.unwrap_or_default());
+            return false;
+        }
+
+        let mime_type = saved.media_mime_type.as_deref().unwrap_or("application/octet-stream");
+        mime_allowed(mime_type, &self.config.allowed_mime_types, &self.config.denied_mime_types)
+    }
+
+    pub fn spawn(&self, message: Message, saved: TelegramMessage) -> JoinHandle<()> {
+        let downloader = self.clone();
+
+        tokio::spawn(async move {
+            let _permit = match downloader.permits.acquire().await {
+                Ok(permit) => permit,
+                Err(_) => return,
+            };
+
+            match downloader.download(&message, &saved).await {
+                Ok(media) => info!("📎 Mídia salva: mensagem {} ({} bytes, sha256 {})",
+                    saved.telegram_message_id, media.file_size, media.sha256),
+                Err(e) => warn!("❌ Erro ao baixar mídia da mensagem {}: {}", saved.telegram_message_id, e),
+            }
+        })
+    }
+
+    async fn download(&self, message: &Message, saved: &TelegramMessage) -> Result<TelegramMedia, MediaError> {
+        let temp_path = self.store.temp_path();
+        if let Some(parent) = temp_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        if !message.download_media(&temp_path).await? {
+            return Err("mensagem sem mídia para baixar".into());
+        }
+
+        // O tamanho informado pelo Telegram nem sempre existe (ex.: fotos), então confere o arquivo baixado
+        let size = tokio::fs::metadata(&temp_path).await?.len() as i64;
+        if size > self.config.max_file_size {
+            tokio::fs::remove_file(&temp_path).await.ok();
+            return Err(format!("arquivo com {} bytes excede o limite de {} bytes", size, self.config.max_file_size).into());
+        }
+
+        let stored = match self.store.ingest(&temp_path).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                tokio::fs::remove_file(&temp_path).await.ok();
+                return Err(e);
+            }
+        };
+
+        let new_media = NewTelegramMedia {
+            message_id: saved.id,
+            sha256: stored.sha256,
+            sha1: stored.sha1,
+            md5: stored.md5,
+            file_size: stored.size,
+            mime_type: saved.media_mime_type.clone(),
+            file_name: saved.media_file_name.clone(),
+            storage_path: stored.path.to_string_lossy().to_string(),
+        };
+
+        Ok(TelegramMedia::create(self.database.get_pool(), new_media).await?)
+    }
+}
+
+fn chat_enabled(enabled_chats: &[String], group: &TelegramGroup) -> bool {
+    if enabled_chats.is_empty() {
+        return true;
+    }
+
+    enabled_chats.iter().any(|chat| {
+        chat.parse::<i64>().is_ok_and(|id| id == group.telegram_chat_id)
+            || group.username.as_deref().is_some_and(|username| username.eq_ignore_ascii_case(chat.trim_start_matches('@')))
+    })
+}
+
+// Padrões aceitam curinga no subtipo ("image/*") ou "*" para qualquer tipo; a lista de bloqueio tem prioridade
+pub fn mime_allowed(mime_type: &str, allowed: &[String], denied: &[String]) -> bool {
+    let matches = |pattern: &String| {
+        pattern == "*"
+            || pattern.eq_ignore_ascii_case(mime_type)
+            || pattern.strip_suffix("/*").is_some_and(|prefix| {
+                mime_type.split('/').next().is_some_and(|kind| kind.eq_ignore_ascii_case(prefix))
+            })
+    };
+
+    if denied.iter().any(matches) {
+        return false;
+    }
+
+    allowed.is_empty() || allowed.iter().any(matches)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,51 @@
+use super::*;
+
+fn patterns(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn empty_lists_allow_everything() {
+    assert!(mime_allowed("application/x-msdownload", &[], &[]));
+}
+
+#[test]
+fn allow_list_supports_wildcards() {
+    let allowed = patterns(&["image/*", "application/zip"]);
+    assert!(mime_allowed("image/png", &allowed, &[]));
+    assert!(mime_allowed("application/zip", &allowed, &[]));
+    assert!(!mime_allowed("video/mp4", &allowed, &[]));
+}
+
+#[test]
+fn deny_list_wins_over_allow_list() {
+    let allowed = patterns(&["*"]);
+    let denied = patterns(&["video/*"]);
+    assert!(!mime_allowed("video/mp4", &allowed, &denied));
+    assert!(mime_allowed("image/jpeg", &allowed, &denied));
+}
+
+#[tokio::test]
+async fn content_store_hashes_and_deduplicates_files() {
+    let root = tempfile::tempdir().expect("falha ao criar diretório temporário");
+    let store = ContentStore::new(root.path());
+
+    let mut paths = Vec::new();
+    for _ in 0..2 {
+        let temp_path = store.temp_path();
+        tokio::fs::create_dir_all(temp_path.parent().unwrap()).await.unwrap();
+        tokio::fs::write(&temp_path, b"abc").await.unwrap();
+
+        let stored = store.ingest(&temp_path).await.expect("falha ao armazenar arquivo");
+        assert_eq!(stored.sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(stored.sha1, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(stored.md5, "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(stored.size, 3);
+        assert!(!temp_path.exists());
+        paths.push(stored.path);
+    }
+
+    assert_eq!(paths[0], paths[1]);
+    assert_eq!(paths[0], root.path().join("ba").join("78").join("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+    assert_eq!(tokio::fs::read(&paths[0]).await.unwrap(), b"abc");
+}
@@ -0,0 +1,266 @@
+use crate::config::NotifyConfig;
+use crate::db::{Alert, AlertDetails, Database, NotificationDelivery};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+mod smtp;
+mod syslog;
+mod telegram;
+mod webhook;
+
+pub use smtp::SmtpNotifier;
+pub use syslog::SyslogNotifier;
+pub use telegram::TelegramNotifier;
+pub use webhook::WebhookNotifier;
+
+pub type NotifyError = Box<dyn std::error::Error + Send + Sync>;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const EXCERPT_RADIUS: usize = 80;
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn notify(&self, alert: &AlertDetails) -> Result<(), NotifyError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeliveryOutcome {
+    Sent { attempts: u32 },
+    Failed { attempts: u32, error: String },
+    RateLimited,
+}
+
+impl DeliveryOutcome {
+    pub fn status(&self) -> &'static str {
+        match self {
+            DeliveryOutcome::Sent { .. } => "sent",
+            DeliveryOutcome::Failed { .. } => "failed",
+            DeliveryOutcome::RateLimited => "rate_limited",
+        }
+    }
+}
+
+// Balde de fichas: permite rajadas até o limite por minuto e repõe continuamente
+pub struct RateLimiter {
+    per_minute: u32,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute,
+            state: Mutex::new((per_minute as f64, Instant::now())),
+        }
+    }
+
+    pub fn try_acquire(&self) -> bool {
+        if self.per_minute == 0 {
+            return true;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (tokens, refilled_at) = &mut *state;
+        let capacity = self.per_minute as f64;
+        *tokens = (*tokens + refilled_at.elapsed().as_secs_f64() * capacity / 60.0).min(capacity);
+        *refilled_at = Instant::now();
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+struct ManagedNotifier {
+    notifier: Arc<dyn Notifier>,
+    limiter: RateLimiter,
+}
+
+#[derive(Clone)]
+pub struct NotificationDispatcher {
+    database: Database,
+    notifiers: Vec<Arc<ManagedNotifier>>,
+    rate_limit_per_minute: u32,
+    max_attempts: u32,
+    backoff: Duration,
+}
+
+impl NotificationDispatcher {
+    pub fn new(database: Database, config: &NotifyConfig) -> Result<Self, NotifyError> {
+        let mut dispatcher = Self {
+            database,
+            notifiers: Vec::new(),
+            rate_limit_per_minute: config.rate_limit_per_minute,
+            max_attempts: config.max_attempts.max(1),
+            backoff: Duration::from_millis(config.retry_backoff_ms),
+        };
+
+        if let Some(webhook) = &config.webhook {
+            dispatcher = dispatcher.with_notifier(Arc::new(WebhookNotifier::new(&webhook.url)?));
+        }
+        if let Some(smtp) = &config.smtp {
+            dispatcher = dispatcher.with_notifier(Arc::new(SmtpNotifier::new(smtp)?));
+        }
+        if let Some(syslog) = &config.syslog {
+            dispatcher = dispatcher.with_notifier(Arc::new(SyslogNotifier::new(syslog)));
+        }
+
+        Ok(dispatcher)
+    }
+
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifiers.push(Arc::new(ManagedNotifier {
+            notifier,
+            limiter: RateLimiter::new(self.rate_limit_per_minute),
+        }));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+
+    // As entregas rodam fora do caminho de ingestão para não atrasar o processamento das mensagens
+    pub fn spawn(&self, alerts: Vec<Alert>) -> JoinHandle<()> {
+        let dispatcher = self.clone();
+
+        tokio::spawn(async move {
+            for alert in alerts {
+                if let Err(e) = dispatcher.dispatch(&alert).await {
+                    warn!("❌ Erro ao notificar alerta {}: {}", alert.id, e);
+                }
+            }
+        })
+    }
+
+    pub async fn dispatch(&self, alert: &Alert) -> Result<Vec<NotificationDelivery>, sqlx::Error> {
+        let pool = self.database.get_pool();
+        let Some(details) = AlertDetails::find_by_alert_id(pool, alert.id).await? else {
+            return Ok(Vec::new());
+        };
+
+        let mut deliveries = Vec::new();
+        for managed in &self.notifiers {
+            let outcome = if managed.limiter.try_acquire() {
+                deliver_with_retry(managed.notifier.as_ref(), &details, self.max_attempts, self.backoff).await
+            } else {
+                DeliveryOutcome::RateLimited
+            };
+
+            let (attempts, error) = match &outcome {
+                DeliveryOutcome::Sent { attempts } => (*attempts, None),
+                DeliveryOutcome::Failed { attempts, error } => (*attempts, Some(error.clone())),
+                DeliveryOutcome::RateLimited => (0, None),
+            };
+
+            match &outcome {
+                DeliveryOutcome::Sent { .. } => info!("📨 Alerta {} enviado via {}", alert.id, managed.notifier.name()),
+                DeliveryOutcome::Failed { error, .. } => warn!("❌ Falha ao enviar alerta {} via {}: {}", alert.id, managed.notifier.name(), error),
+                DeliveryOutcome::RateLimited => warn!("⏳ Alerta {} não enviado via {}: limite de envios atingido", alert.id, managed.notifier.name()),
+            }
+
+            deliveries.push(NotificationDelivery::record(
+                pool,
+                alert.id,
+                managed.notifier.name(),
+                outcome.status(),
+                attempts as i32,
+                error,
+            ).await?);
+        }
+
+        Ok(deliveries)
+    }
+}
+
+pub async fn deliver_with_retry(
+    notifier: &dyn Notifier,
+    alert: &AlertDetails,
+    max_attempts: u32,
+    backoff: Duration,
+) -> DeliveryOutcome {
+    let mut attempt = 1;
+
+    loop {
+        match notifier.notify(alert).await {
+            Ok(()) => return DeliveryOutcome::Sent { attempts: attempt },
+            Err(e) if attempt >= max_attempts => {
+                return DeliveryOutcome::Failed { attempts: attempt, error: e.to_string() };
+            },
+            Err(e) => {
+                let delay = backoff.saturating_mul(2u32.saturating_pow(attempt - 1)).min(MAX_BACKOFF);
+                warn!("🔁 Tentativa {} via {} falhou ({}); nova tentativa em {:?}", attempt, notifier.name(), e, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            },
+        }
+    }
+}
+
+// Trecho da mensagem ao redor da ocorrência: (antes, ocorrência, depois), em caracteres
+pub fn excerpt(text: &str, start: i32, end: i32, radius: usize) -> (String, String, String) {
+    let chars: Vec<char> = text.chars().collect();
+    let start = (start.max(0) as usize).min(chars.len());
+    let end = (end.max(0) as usize).clamp(start, chars.len());
+    let from = start.saturating_sub(radius);
+    let to = (end + radius).min(chars.len());
+
+    let mut before: String = chars[from..start].iter().collect();
+    if from > 0 {
+        before.insert(0, '…');
+    }
+    let mut after: String = chars[end..to].iter().collect();
+    if to < chars.len() {
+        after.push('…');
+    }
+
+    (before, chars[start..end].iter().collect(), after)
+}
+
+pub fn chat_label(alert: &AlertDetails) -> String {
+    match (&alert.group_title, &alert.group_username) {
+        (Some(title), Some(username)) => format!("{} (@{})", title, username),
+        (Some(title), None) => title.clone(),
+        (None, Some(username)) => format!("@{}", username),
+        (None, None) => alert.telegram_chat_id.map_or_else(|| "chat desconhecido".to_string(), |id| id.to_string()),
+    }
+}
+
+pub fn sender_label(alert: &AlertDetails) -> String {
+    match (&alert.sender_name, &alert.sender_username) {
+        (Some(name), Some(username)) => format!("{} (@{})", name, username),
+        (Some(name), None) => name.clone(),
+        (None, Some(username)) => format!("@{}", username),
+        (None, None) => alert.sender_telegram_id.map_or_else(|| "remetente desconhecido".to_string(), |id| id.to_string()),
+    }
+}
+
+// Texto simples compartilhado por e-mail e syslog; o webhook monta a própria formatação
+pub fn plain_summary(alert: &AlertDetails) -> String {
+    let (before, matched, after) = excerpt(alert.message_text.as_deref().unwrap_or_default(), alert.match_start, alert.match_end, EXCERPT_RADIUS);
+    let mut summary = format!(
+        "Regra: {}\nChat: {}\nRemetente: {}\nData: {}\nTrecho: {}[{}]{}",
+        alert.rule_name,
+        chat_label(alert),
+        sender_label(alert),
+        alert.message_date.to_rfc3339(),
+        before,
+        matched,
+        after,
+    );
+    if let Some(link) = alert.message_link() {
+        summary.push_str(&format!("\nLink: {}", link));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,58 @@
+use super::{chat_label, plain_summary, Notifier, NotifyError};
+use crate::config::{SmtpConfig, SmtpTls};
+use crate::db::AlertDetails;
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+pub struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl SmtpNotifier {
+    pub fn new(config: &SmtpConfig) -> Result<Self, NotifyError> {
+        let builder = match config.tls {
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        };
+        let builder = match (&config.username, &config.password) {
+            (Some(username), Some(password)) => builder.credentials(Credentials::new(username.clone(), password.clone())),
+            _ => builder,
+        };
+
+        if config.to.is_empty() {
+            return Err("NOTIFY_SMTP_TO precisa de ao menos um destinatário".into());
+        }
+
+        Ok(Self {
+            transport: builder.port(config.port).build(),
+            from: config.from.parse()?,
+            to: config.to.iter().map(|to| to.parse()).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for SmtpNotifier {
+    fn name(&self) -> &str {
+        "smtp"
+    }
+
+    async fn notify(&self, alert: &AlertDetails) -> Result<(), NotifyError> {
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(format!("[f1000] {}: '{}' em {}", alert.rule_name, alert.matched_text, chat_label(alert)))
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+
+        self.transport.send(message.body(plain_summary(alert))?).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use super::{chat_label, Notifier, NotifyError};
+use crate::config::{SyslogConfig, SyslogProtocol};
+use crate::db::AlertDetails;
+use async_trait::async_trait;
+use chrono::SecondsFormat;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+
+// local0 (16) com severidade warning (4)
+const PRIORITY: u8 = 16 * 8 + 4;
+// Número de empresa privado de exemplo da RFC 5424 para o SD-ID
+const SD_ID: &str = "f1000@32473";
+
+pub struct SyslogNotifier {
+    address: String,
+    protocol: SyslogProtocol,
+    app_name: String,
+    hostname: String,
+}
+
+impl SyslogNotifier {
+    pub fn new(config: &SyslogConfig) -> Self {
+        Self {
+            address: config.address.clone(),
+            protocol: config.protocol,
+            app_name: config.app_name.clone(),
+            hostname: hostname(),
+        }
+    }
+
+    // RFC 5424: <PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID [SD] MSG
+    pub fn format(&self, alert: &AlertDetails) -> String {
+        let params = [
+            ("alert_id", alert.alert_id.to_string()),
+            ("rule", alert.rule_name.clone()),
+            ("matched", alert.matched_text.clone()),
+            ("chat_id", alert.telegram_chat_id.map(|id| id.to_string()).unwrap_or_default()),
+            ("message_id", alert.telegram_message_id.to_string()),
+            ("link", alert.message_link().unwrap_or_default()),
+        ];
+        let structured_data = params.iter()
+            .map(|(name, value)| format!(" {}=\"{}\"", name, escape_param(value)))
+            .collect::<String>();
+
+        format!(
+            "<{}>1 {} {} {} {} ALERT [{}{}] \u{feff}Watchlist {}: '{}' em {}",
+            PRIORITY,
+            chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            header_field(&self.hostname),
+            header_field(&self.app_name),
+            std::process::id(),
+            SD_ID,
+            structured_data,
+            alert.rule_name,
+            alert.matched_text,
+            chat_label(alert),
+        )
+    }
+}
+
+#[async_trait]
+impl Notifier for SyslogNotifier {
+    fn name(&self) -> &str {
+        "syslog"
+    }
+
+    async fn notify(&self, alert: &AlertDetails) -> Result<(), NotifyError> {
+        let message = self.format(alert);
+
+        match self.protocol {
+            SyslogProtocol::Udp => {
+                let bind = if self.address.starts_with('[') { "[::]:0" } else { "0.0.0.0:0" };
+                let socket = UdpSocket::bind(bind).await?;
+                socket.send_to(message.as_bytes(), &self.address).await?;
+            },
+            SyslogProtocol::Tcp => {
+                // RFC 6587: enquadramento por contagem de octetos
+                let mut stream = TcpStream::connect(&self.address).await?;
+                stream.write_all(format!("{} {}", message.len(), message).as_bytes()).await?;
+                stream.shutdown().await?;
+            },
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn escape_param(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace(']', "\\]")
+}
+
+// Campos do cabeçalho são ASCII imprimível sem espaços; vazio vira "-"
+fn header_field(value: &str) -> String {
+    let field: String = value.chars().filter(|c| c.is_ascii_graphic()).take(48).collect();
+    if field.is_empty() { "-".to_string() } else { field }
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| "-".to_string())
+}
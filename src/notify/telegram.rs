@@ -0,0 +1,87 @@
+use super::{excerpt, sender_label, Notifier, NotifyError, EXCERPT_RADIUS};
+use crate::db::AlertDetails;
+use crate::telegram::list_dialog_chats;
+use async_trait::async_trait;
+use grammers_client::types::{Chat, InputMessage, PackedChat};
+use grammers_client::Client;
+
+// Publica o alerta num chat privado usando a mesma conta que coleta as mensagens
+pub struct TelegramNotifier {
+    client: Client,
+    chat: PackedChat,
+    chat_id: i64,
+}
+
+impl TelegramNotifier {
+    pub async fn resolve(client: Client, target: &str) -> Result<Self, NotifyError> {
+        let chat = match target.parse::<i64>() {
+            Ok(id) => {
+                let id = bare_chat_id(id);
+                list_dialog_chats(&client).await?
+                    .into_iter()
+                    .find(|chat: &Chat| chat.id() == id)
+            },
+            Err(_) => client.resolve_username(target.trim_start_matches('@')).await?,
+        };
+        let chat = chat.ok_or_else(|| format!("chat de alertas '{}' não encontrado entre os diálogos da conta", target))?;
+
+        Ok(Self {
+            chat_id: chat.id(),
+            chat: chat.pack(),
+            client,
+        })
+    }
+
+    pub fn chat_id(&self) -> i64 {
+        self.chat_id
+    }
+
+    pub fn format_html(alert: &AlertDetails) -> String {
+        let (before, matched, after) = excerpt(alert.message_text.as_deref().unwrap_or_default(), alert.match_start, alert.match_end, EXCERPT_RADIUS);
+        let group = alert.group_title.clone()
+            .or_else(|| alert.group_username.as_ref().map(|username| format!("@{}", username)))
+            .unwrap_or_else(|| "chat desconhecido".to_string());
+
+        let mut html = format!(
+            "🚨 <b>Watchlist: {}</b>\n<b>Grupo:</b> {}\n<b>Remetente:</b> {}\n\n{}<b><u>{}</u></b>{}",
+            escape_html(&alert.rule_name),
+            escape_html(&group),
+            escape_html(&sender_label(alert)),
+            escape_html(&before),
+            escape_html(&matched),
+            escape_html(&after),
+        );
+        if let Some(link) = alert.message_link() {
+            html.push_str(&format!("\n\n<a href=\"{}\">Abrir mensagem</a>", escape_html(&link)));
+        }
+        html
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn notify(&self, alert: &AlertDetails) -> Result<(), NotifyError> {
+        let message = InputMessage::html(Self::format_html(alert)).link_preview(false);
+        self.client.send_message(self.chat, message).await?;
+        Ok(())
+    }
+}
+
+// Aceita o ID no formato da API de bots (-100…) ou o ID puro que o grammers usa
+pub fn bare_chat_id(id: i64) -> i64 {
+    const CHANNEL_PREFIX: i64 = 1_000_000_000_000;
+
+    if id <= -CHANNEL_PREFIX {
+        -id - CHANNEL_PREFIX
+    } else {
+        id.abs()
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,262 @@
+use super::*;
+use crate::config::{SmtpConfig, SmtpTls, SyslogConfig, SyslogProtocol};
+use chrono::Utc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, UdpSocket};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+fn alert_details() -> AlertDetails {
+    AlertDetails {
+        alert_id: Uuid::new_v4(),
+        rule_name: "marca".to_string(),
+        match_type: "case_insensitive".to_string(),
+        match_start: 16,
+        match_end: 20,
+        matched_text: "ACME".to_string(),
+        message_id: Uuid::new_v4(),
+        telegram_message_id: 321,
+        message_text: Some("vendendo logins ACME com \"2FA\"".to_string()),
+        message_date: Utc::now(),
+        telegram_chat_id: Some(1234567890),
+        group_title: Some("Mercado".to_string()),
+        group_username: None,
+        chat_type: Some("supergroup".to_string()),
+        sender_telegram_id: Some(42),
+        sender_username: Some("vendedor".to_string()),
+        sender_name: Some("Fulano".to_string()),
+        created_at: Utc::now(),
+    }
+}
+
+struct FlakyNotifier {
+    failures: u32,
+    calls: AtomicU32,
+}
+
+#[async_trait]
+impl Notifier for FlakyNotifier {
+    fn name(&self) -> &str {
+        "flaky"
+    }
+
+    async fn notify(&self, _alert: &AlertDetails) -> Result<(), NotifyError> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            Err("indisponível".into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// Servidor HTTP mínimo: responde com os status informados, um por conexão, e repassa os corpos recebidos
+async fn http_stand_in(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        for status in statuses {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).await.unwrap();
+            sender.send(String::from_utf8(body).unwrap()).ok();
+
+            let response = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            reader.into_inner().write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    (format!("http://{}/hook", address), receiver)
+}
+
+#[test]
+fn excerpt_marks_the_match_and_truncates() {
+    let (before, matched, after) = excerpt("abcdefghij", 4, 6, 2);
+    assert_eq!((before.as_str(), matched.as_str(), after.as_str()), ("…cd", "ef", "gh…"));
+
+    let (before, matched, after) = excerpt("ação", 0, 4, 10);
+    assert_eq!((before.as_str(), matched.as_str(), after.as_str()), ("", "ação", ""));
+}
+
+#[test]
+fn rate_limiter_blocks_after_the_burst() {
+    let limiter = RateLimiter::new(2);
+    assert!(limiter.try_acquire());
+    assert!(limiter.try_acquire());
+    assert!(!limiter.try_acquire());
+
+    let unlimited = RateLimiter::new(0);
+    assert!((0..100).all(|_| unlimited.try_acquire()));
+}
+
+#[tokio::test]
+async fn retries_until_the_notifier_succeeds() {
+    let notifier = FlakyNotifier { failures: 2, calls: AtomicU32::new(0) };
+    let outcome = deliver_with_retry(&notifier, &alert_details(), 3, Duration::from_millis(1)).await;
+    assert_eq!(outcome, DeliveryOutcome::Sent { attempts: 3 });
+
+    let notifier = FlakyNotifier { failures: 5, calls: AtomicU32::new(0) };
+    let outcome = deliver_with_retry(&notifier, &alert_details(), 2, Duration::from_millis(1)).await;
+    assert_eq!(outcome, DeliveryOutcome::Failed { attempts: 2, error: "indisponível".to_string() });
+}
+
+#[tokio::test]
+async fn webhook_posts_slack_compatible_json_and_retries_server_errors() {
+    let (url, mut bodies) = http_stand_in(vec![500, 200]).await;
+    let notifier = WebhookNotifier::new(&url).unwrap();
+    let alert = alert_details();
+
+    let outcome = deliver_with_retry(&notifier, &alert, 3, Duration::from_millis(1)).await;
+    assert_eq!(outcome, DeliveryOutcome::Sent { attempts: 2 });
+
+    bodies.recv().await.unwrap();
+    let payload: serde_json::Value = serde_json::from_str(&bodies.recv().await.unwrap()).unwrap();
+    let text = payload["text"].as_str().unwrap();
+    assert!(text.contains("`ACME`"));
+    assert!(text.contains("https://t.me/c/1234567890/321"));
+    assert_eq!(payload["alert"]["rule"], "marca");
+    assert_eq!(payload["alert"]["sender_username"], "vendedor");
+}
+
+#[tokio::test]
+async fn smtp_sends_the_alert_to_every_recipient() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, mut transcript) = mpsc::unbounded_channel();
+
+    // Servidor SMTP mínimo que aceita uma mensagem e devolve a conversa recebida
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read, mut write) = stream.into_split();
+        let mut reader = BufReader::new(read);
+        let mut received = String::new();
+        let mut in_data = false;
+        write.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await.unwrap() == 0 {
+                break;
+            }
+            received.push_str(&line);
+            let reply: &[u8] = if in_data {
+                if line != ".\r\n" {
+                    continue;
+                }
+                in_data = false;
+                b"250 OK\r\n"
+            } else if line.starts_with("EHLO") {
+                b"250-localhost\r\n250 8BITMIME\r\n"
+            } else if line.starts_with("DATA") {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if line.starts_with("QUIT") {
+                write.write_all(b"221 bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 OK\r\n"
+            };
+            write.write_all(reply).await.unwrap();
+        }
+        sender.send(received).ok();
+    });
+
+    let notifier = SmtpNotifier::new(&SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        tls: SmtpTls::None,
+        username: None,
+        password: None,
+        from: "f1000@example.com".to_string(),
+        to: vec!["soc@example.com".to_string(), "analista@example.com".to_string()],
+    })
+    .unwrap();
+
+    notifier.notify(&alert_details()).await.expect("falha ao enviar e-mail");
+    drop(notifier);
+
+    let received = transcript.recv().await.unwrap();
+    assert!(received.contains("RCPT TO:<soc@example.com>"));
+    assert!(received.contains("RCPT TO:<analista@example.com>"));
+    assert!(received.contains("Subject: [f1000] marca: 'ACME' em Mercado"));
+    assert!(received.contains("[ACME]"));
+}
+
+#[tokio::test]
+async fn syslog_sends_rfc5424_over_udp() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let notifier = SyslogNotifier::new(&SyslogConfig {
+        address: socket.local_addr().unwrap().to_string(),
+        protocol: SyslogProtocol::Udp,
+        app_name: "f1000".to_string(),
+    });
+
+    notifier.notify(&alert_details()).await.expect("falha ao enviar syslog");
+
+    let mut buffer = vec![0u8; 4096];
+    let read = socket.recv(&mut buffer).await.unwrap();
+    let message = String::from_utf8(buffer[..read].to_vec()).unwrap();
+    assert!(message.starts_with("<132>1 "));
+    assert!(message.contains(" f1000 "));
+    assert!(message.contains("[f1000@32473 alert_id="));
+    assert!(message.contains("rule=\"marca\""));
+    assert!(message.ends_with("Watchlist marca: 'ACME' em Mercado"));
+}
+
+#[tokio::test]
+async fn syslog_frames_tcp_messages_by_octet_count() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let notifier = SyslogNotifier::new(&SyslogConfig {
+        address: listener.local_addr().unwrap().to_string(),
+        protocol: SyslogProtocol::Tcp,
+        app_name: "f1000".to_string(),
+    });
+
+    let alert = alert_details();
+    let (sent, accepted) = tokio::join!(notifier.notify(&alert), listener.accept());
+    sent.expect("falha ao enviar syslog");
+
+    let mut received = String::new();
+    accepted.unwrap().0.read_to_string(&mut received).await.unwrap();
+    let (length, message) = received.split_once(' ').unwrap();
+    assert_eq!(length.parse::<usize>().unwrap(), message.len());
+    assert!(message.starts_with("<132>1 "));
+}
+
+#[test]
+fn syslog_escapes_structured_data_values() {
+    assert_eq!(syslog::escape_param(r#"a"b]c\d"#), r#"a\"b\]c\\d"#);
+}
+
+#[test]
+fn telegram_alert_highlights_the_match_and_links_the_message() {
+    let mut alert = alert_details();
+    alert.group_title = Some("Mercado <VIP>".to_string());
+
+    let html = TelegramNotifier::format_html(&alert);
+    assert!(html.contains("<b>Grupo:</b> Mercado &lt;VIP&gt;"));
+    assert!(html.contains("<b>Remetente:</b> Fulano (@vendedor)"));
+    assert!(html.contains("vendendo logins <b><u>ACME</u></b> com &quot;2FA&quot;"));
+    assert!(html.contains("<a href=\"https://t.me/c/1234567890/321\">Abrir mensagem</a>"));
+}
+
+#[test]
+fn telegram_alert_chat_accepts_bot_api_ids() {
+    assert_eq!(telegram::bare_chat_id(-1001234567890), 1234567890);
+    assert_eq!(telegram::bare_chat_id(-4567), 4567);
+    assert_eq!(telegram::bare_chat_id(777), 777);
+}
@@ -0,0 +1,75 @@
+use super::{chat_label, excerpt, sender_label, Notifier, NotifyError, EXCERPT_RADIUS};
+use crate::db::AlertDetails;
+use async_trait::async_trait;
+use serde_json::json;
+use std::time::Duration;
+
+// Slack, Teams e Mattermost aceitam o campo "text"; o objeto "alert" leva os dados estruturados
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str) -> Result<Self, NotifyError> {
+        Ok(Self {
+            url: url.to_string(),
+            client: reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?,
+        })
+    }
+
+    pub fn payload(alert: &AlertDetails) -> serde_json::Value {
+        let (before, matched, after) = excerpt(alert.message_text.as_deref().unwrap_or_default(), alert.match_start, alert.match_end, EXCERPT_RADIUS);
+        let mut text = format!(
+            "🚨 Watchlist *{}* em {}\nRemetente: {}\n> {}`{}`{}",
+            alert.rule_name,
+            chat_label(alert),
+            sender_label(alert),
+            before.replace('\n', " "),
+            matched,
+            after.replace('\n', " "),
+        );
+        let link = alert.message_link();
+        if let Some(link) = &link {
+            text.push_str(&format!("\n{}", link));
+        }
+
+        json!({
+            "text": text,
+            "alert": {
+                "id": alert.alert_id,
+                "rule": alert.rule_name,
+                "match_type": alert.match_type,
+                "matched_text": alert.matched_text,
+                "match_start": alert.match_start,
+                "match_end": alert.match_end,
+                "chat_id": alert.telegram_chat_id,
+                "chat_title": alert.group_title,
+                "chat_username": alert.group_username,
+                "sender_id": alert.sender_telegram_id,
+                "sender_username": alert.sender_username,
+                "message_id": alert.telegram_message_id,
+                "message_date": alert.message_date,
+                "link": link,
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn notify(&self, alert: &AlertDetails) -> Result<(), NotifyError> {
+        self.client
+            .post(&self.url)
+            .json(&Self::payload(alert))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
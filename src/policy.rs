@@ -0,0 +1,221 @@
+use crate::db::{ChatPolicyRule, Database};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::warn;
+
+// Políticas cadastradas na tabela chat_policies passam a valer sem reiniciar o coletor
+const RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+const CHAT_TYPES: &[&str] = &["private", "group", "supergroup", "channel"];
+
+// Cada modo inclui tudo o que os anteriores guardam; full ainda traz o histórico ao iniciar a coleta
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMode {
+    Ignore,
+    MetadataOnly,
+    Text,
+    #[default]
+    TextMedia,
+    Full,
+}
+
+impl CaptureMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "ignore" => Some(CaptureMode::Ignore),
+            "metadata_only" | "metadata-only" => Some(CaptureMode::MetadataOnly),
+            "text" => Some(CaptureMode::Text),
+            "text_media" | "text+media" => Some(CaptureMode::TextMedia),
+            "full" => Some(CaptureMode::Full),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CaptureMode::Ignore => "ignore",
+            CaptureMode::MetadataOnly => "metadata_only",
+            CaptureMode::Text => "text",
+            CaptureMode::TextMedia => "text_media",
+            CaptureMode::Full => "full",
+        }
+    }
+
+    pub fn stores_text(&self) -> bool {
+        *self >= CaptureMode::Text
+    }
+
+    pub fn downloads_media(&self) -> bool {
+        *self >= CaptureMode::TextMedia
+    }
+
+    pub fn backfills(&self) -> bool {
+        *self == CaptureMode::Full
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatSelector {
+    Id(i64),
+    Username(String),
+    ChatType(String),
+}
+
+impl ChatSelector {
+    // "-1001234567890", "@canal" ou um tipo de chat; nomes sem @ que coincidem com um tipo são o tipo
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Ok(id) = value.parse() {
+            return Some(ChatSelector::Id(id));
+        }
+        if CHAT_TYPES.contains(&value) {
+            return Some(ChatSelector::ChatType(value.to_string()));
+        }
+        let username = value.strip_prefix('@').unwrap_or(value);
+        if username.is_empty() || !username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return None;
+        }
+        Some(ChatSelector::Username(username.to_lowercase()))
+    }
+
+    pub fn from_parts(kind: &str, value: &str) -> Option<Self> {
+        match kind {
+            "id" => value.trim().parse().ok().map(ChatSelector::Id),
+            "username" => Some(ChatSelector::Username(value.trim().trim_start_matches('@').to_lowercase())),
+            "chat_type" if CHAT_TYPES.contains(&value.trim()) => Some(ChatSelector::ChatType(value.trim().to_string())),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ChatSelector::Id(_) => "id",
+            ChatSelector::Username(_) => "username",
+            ChatSelector::ChatType(_) => "chat_type",
+        }
+    }
+
+    pub fn value(&self) -> String {
+        match self {
+            ChatSelector::Id(id) => id.to_string(),
+            ChatSelector::Username(username) => username.clone(),
+            ChatSelector::ChatType(chat_type) => chat_type.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for ChatSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChatSelector::Username(username) => write!(f, "@{}", username),
+            selector => write!(f, "{}", selector.value()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatPolicy {
+    pub selector: ChatSelector,
+    pub mode: CaptureMode,
+}
+
+impl ChatPolicy {
+    // Formato de POLICY_CHATS: "seletor=modo", como "private=ignore" ou "@canal=text"
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let Some((selector, mode)) = value.split_once('=') else {
+            return Err(format!("deve ser seletor=modo (valor: {})", value));
+        };
+        let selector = ChatSelector::parse(selector)
+            .ok_or_else(|| format!("seletor inválido: {} (use o ID, @username ou private, group, supergroup, channel)", selector.trim()))?;
+        let mode = CaptureMode::parse(mode)
+            .ok_or_else(|| format!("modo inválido: {} (use ignore, metadata_only, text, text_media ou full)", mode.trim()))?;
+        Ok(ChatPolicy { selector, mode })
+    }
+}
+
+impl std::fmt::Display for ChatPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.selector, self.mode.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PolicySet {
+    rules: Vec<ChatPolicy>,
+    default: CaptureMode,
+}
+
+impl PolicySet {
+    pub fn new(default: CaptureMode, rules: Vec<ChatPolicy>) -> Self {
+        Self { rules, default }
+    }
+
+    // Linhas da tabela substituem as regras do f1000.toml com o mesmo seletor
+    pub fn with_overrides(&self, rows: &[ChatPolicyRule]) -> Self {
+        let mut policies = self.clone();
+        for row in rows {
+            let (Some(selector), Some(mode)) = (ChatSelector::from_parts(&row.selector_type, &row.selector), CaptureMode::parse(&row.mode)) else {
+                warn!("⚠️ Política de chat ignorada: {} '{}' com modo '{}'", row.selector_type, row.selector, row.mode);
+                continue;
+            };
+            policies.rules.retain(|rule| rule.selector != selector);
+            policies.rules.push(ChatPolicy { selector, mode });
+        }
+        policies
+    }
+
+    // O seletor mais específico vence: ID, depois username, depois tipo do chat
+    pub fn mode_for(&self, chat_id: i64, username: Option<&str>, chat_type: &str) -> CaptureMode {
+        let by_id = self.rules.iter().find(|rule| rule.selector == ChatSelector::Id(chat_id));
+        let by_username = || username.and_then(|username| self.rules.iter().find(|rule| {
+            matches!(&rule.selector, ChatSelector::Username(name) if name.eq_ignore_ascii_case(username))
+        }));
+        let by_chat_type = || self.rules.iter().find(|rule| {
+            matches!(&rule.selector, ChatSelector::ChatType(kind) if kind == chat_type)
+        });
+
+        by_id.or_else(by_username).or_else(by_chat_type)
+            .map(|rule| rule.mode)
+            .unwrap_or(self.default)
+    }
+}
+
+type CachedPolicies = Option<(Instant, Arc<PolicySet>)>;
+
+// Combina as políticas da configuração com as da tabela, recarregadas periodicamente
+#[derive(Clone)]
+pub struct PolicyCache {
+    database: Database,
+    base: Arc<PolicySet>,
+    cached: Arc<RwLock<CachedPolicies>>,
+}
+
+impl PolicyCache {
+    pub fn new(database: Database, base: PolicySet) -> Self {
+        Self {
+            database,
+            base: Arc::new(base),
+            cached: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub async fn current(&self) -> Result<Arc<PolicySet>, sqlx::Error> {
+        if let Some((loaded_at, policies)) = self.cached.read().await.as_ref() {
+            if loaded_at.elapsed() < RELOAD_INTERVAL {
+                return Ok(policies.clone());
+            }
+        }
+
+        let rows = ChatPolicyRule::find_all(self.database.get_pool()).await?;
+        let policies = Arc::new(self.base.with_overrides(&rows));
+        *self.cached.write().await = Some((Instant::now(), policies.clone()));
+
+        Ok(policies)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,61 @@
+use super::*;
+use chrono::Utc;
+use uuid::Uuid;
+
+fn policies(default: CaptureMode, rules: &[&str]) -> PolicySet {
+    PolicySet::new(default, rules.iter().map(|rule| ChatPolicy::parse(rule).unwrap()).collect())
+}
+
+fn row(selector_type: &str, selector: &str, mode: &str) -> ChatPolicyRule {
+    ChatPolicyRule {
+        id: Uuid::new_v4(),
+        selector_type: selector_type.to_string(),
+        selector: selector.to_string(),
+        mode: mode.to_string(),
+        created_at: Utc::now(),
+    }
+}
+
+#[test]
+fn most_specific_selector_wins() {
+    let policies = policies(CaptureMode::Full, &["private=ignore", "@Suporte_Banco=metadata-only", "-1001=text+media", "channel=text"]);
+
+    assert_eq!(policies.mode_for(42, None, "private"), CaptureMode::Ignore);
+    assert_eq!(policies.mode_for(43, Some("suporte_banco"), "private"), CaptureMode::MetadataOnly);
+    assert_eq!(policies.mode_for(-1001, Some("suporte_banco"), "channel"), CaptureMode::TextMedia);
+    assert_eq!(policies.mode_for(-1002, None, "channel"), CaptureMode::Text);
+    assert_eq!(policies.mode_for(-1003, None, "supergroup"), CaptureMode::Full);
+}
+
+#[test]
+fn modes_are_cumulative() {
+    assert!(!CaptureMode::MetadataOnly.stores_text());
+    assert!(CaptureMode::Text.stores_text() && !CaptureMode::Text.downloads_media());
+    assert!(CaptureMode::TextMedia.downloads_media() && !CaptureMode::TextMedia.backfills());
+    assert!(CaptureMode::Full.backfills());
+    // Sem política, a coleta segue como antes: texto e mídia, sem histórico
+    assert_eq!(CaptureMode::default(), CaptureMode::TextMedia);
+}
+
+#[test]
+fn invalid_policies_are_rejected() {
+    assert!(ChatPolicy::parse("private").is_err());
+    assert!(ChatPolicy::parse("private=tudo").is_err());
+    assert!(ChatPolicy::parse("canal exemplo=text").is_err());
+    assert_eq!(ChatPolicy::parse("@private=text").unwrap().selector, ChatSelector::Username("private".to_string()));
+    assert_eq!(ChatPolicy::parse(" group = full ").unwrap().to_string(), "group=full");
+}
+
+#[test]
+fn table_rows_replace_config_rules_with_the_same_selector() {
+    let policies = policies(CaptureMode::Text, &["private=ignore", "@canal=text"]).with_overrides(&[
+        row("chat_type", "private", "metadata_only"),
+        row("id", "-1001", "ignore"),
+        row("username", "@quebrado", "tudo"),
+    ]);
+
+    assert_eq!(policies.mode_for(42, None, "private"), CaptureMode::MetadataOnly);
+    assert_eq!(policies.mode_for(-1001, Some("canal"), "channel"), CaptureMode::Ignore);
+    assert_eq!(policies.mode_for(-1002, Some("canal"), "channel"), CaptureMode::Text);
+    assert_eq!(policies.mode_for(-1003, Some("quebrado"), "channel"), CaptureMode::Text);
+}
@@ -0,0 +1,134 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+// Abaixo dos 30s que systemd e Kubernetes esperam antes do SIGKILL
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(25);
+
+// Código de saída convencional de quem é interrompido por sinal sem conseguir encerrar direito
+const FORCED_EXIT_CODE: i32 = 130;
+
+#[derive(Clone)]
+pub struct Shutdown {
+    requested: Arc<watch::Sender<bool>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self { requested: Arc::new(watch::Sender::new(false)) }
+    }
+
+    pub fn trigger(&self) {
+        self.requested.send_replace(true);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    pub async fn requested(&self) {
+        let mut receiver = self.requested.subscribe();
+        if receiver.wait_for(|requested| *requested).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    // O primeiro SIGINT/SIGTERM pede o encerramento; um segundo aborta sem esperar as tarefas
+    pub fn listen_for_signals(&self) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            let signal = wait_for_signal().await;
+            info!("🛑 {} recebido: parando a coleta e concluindo o trabalho pendente...", signal);
+            shutdown.trigger();
+
+            let signal = wait_for_signal().await;
+            warn!("⚠️ {} recebido novamente: encerrando sem aguardar as tarefas pendentes", signal);
+            std::process::exit(FORCED_EXIT_CODE);
+        });
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => tokio::select! {
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+            _ = terminate.recv() => "SIGTERM",
+        },
+        Err(e) => {
+            warn!("⚠️ Não foi possível registrar o SIGTERM: {}", e);
+            tokio::signal::ctrl_c().await.ok();
+            "SIGINT"
+        },
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> &'static str {
+    tokio::signal::ctrl_c().await.ok();
+    "Ctrl+C"
+}
+
+// Tarefas disparadas fora do caminho de ingestão (downloads, notificações) que precisam terminar antes de sair
+#[derive(Clone, Default)]
+pub struct TaskTracker {
+    handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl TaskTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track(&self, handle: JoinHandle<()>) {
+        let mut handles = self.handles.lock().unwrap();
+        handles.retain(|handle| !handle.is_finished());
+        handles.push(handle);
+    }
+
+    pub fn pending(&self) -> usize {
+        self.handles.lock().unwrap().iter().filter(|handle| !handle.is_finished()).count()
+    }
+
+    // Devolve quantas tarefas foram abortadas por não terminarem dentro do prazo
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            let handles: Vec<JoinHandle<()>> = std::mem::take(&mut *self.handles.lock().unwrap());
+            if handles.is_empty() {
+                return 0;
+            }
+            info!("⏳ Aguardando {} tarefa(s) em andamento...", handles.len());
+
+            // Tarefas em andamento podem disparar outras (ex.: notificações), por isso a lista é relida
+            let mut handles = handles.into_iter();
+            while let Some(mut handle) = handles.next() {
+                if tokio::time::timeout_at(deadline, &mut handle).await.is_ok() {
+                    continue;
+                }
+
+                let mut unfinished: Vec<JoinHandle<()>> = std::iter::once(handle).chain(handles).collect();
+                unfinished.extend(self.handles.lock().unwrap().drain(..));
+                unfinished.retain(|handle| !handle.is_finished());
+                unfinished.iter().for_each(JoinHandle::abort);
+
+                warn!("⚠️ {} tarefa(s) abortada(s) após {:?} de espera", unfinished.len(), timeout);
+                return unfinished.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
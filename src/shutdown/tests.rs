@@ -0,0 +1,49 @@
+use super::*;
+
+#[tokio::test]
+async fn requested_resolves_after_trigger() {
+    let shutdown = Shutdown::new();
+    assert!(!shutdown.is_requested());
+
+    let waiter = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move { shutdown.requested().await }
+    });
+    shutdown.trigger();
+
+    tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+    assert!(shutdown.is_requested());
+    // Quem chega depois do pedido não fica esperando
+    tokio::time::timeout(Duration::from_secs(1), shutdown.requested()).await.unwrap();
+}
+
+#[tokio::test]
+async fn drain_waits_for_tasks_spawned_while_draining() {
+    let tasks = TaskTracker::new();
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+    let inner = tasks.clone();
+    tasks.track(tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let sender = sender.clone();
+        inner.track(tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            sender.send("notificação").unwrap();
+        }));
+    }));
+
+    assert_eq!(tasks.drain(Duration::from_secs(5)).await, 0);
+    assert_eq!(receiver.try_recv().unwrap(), "notificação");
+    assert_eq!(tasks.pending(), 0);
+}
+
+#[tokio::test]
+async fn drain_aborts_tasks_that_miss_the_deadline() {
+    let tasks = TaskTracker::new();
+    tasks.track(tokio::spawn(async {}));
+    tasks.track(tokio::spawn(std::future::pending()));
+    tasks.track(tokio::spawn(std::future::pending()));
+
+    assert_eq!(tasks.drain(Duration::from_millis(50)).await, 2);
+    assert_eq!(tasks.pending(), 0);
+}
@@ -0,0 +1,304 @@
+use crate::config::TaxiiConfig;
+use crate::db::{CollectedIoc, CollectedIocQuery, Cursor, Database, TelegramGroup};
+use crate::export::stix;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+pub const TAXII_MEDIA_TYPE: &str = "application/taxii+json;version=2.1";
+pub const STIX_MEDIA_TYPE: &str = "application/stix+json;version=2.1";
+pub const ALL_COLLECTION_ALIAS: &str = "all";
+
+const API_ROOT_PATH: &str = "/f1000/";
+const MAX_CONTENT_LENGTH: u64 = 10 * 1024 * 1024;
+
+#[derive(Clone)]
+struct TaxiiState {
+    database: Database,
+    config: Arc<TaxiiConfig>,
+}
+
+pub fn router(database: Database, config: TaxiiConfig) -> Router {
+    let state = TaxiiState { database, config: Arc::new(config) };
+
+    Router::new()
+        .route("/taxii2/", get(discovery))
+        .route(API_ROOT_PATH, get(api_root))
+        .route("/f1000/collections/", get(collections))
+        .route("/f1000/collections/:id/", get(collection))
+        .route("/f1000/collections/:id/objects/", get(objects))
+        .layer(middleware::from_fn_with_state(state.clone(), require_auth))
+        .with_state(state)
+}
+
+pub async fn serve(database: Database, config: TaxiiConfig) -> Result<(), Box<dyn std::error::Error>> {
+    // Servidor sem credenciais exporia toda a coleta: recusa iniciar
+    if !config.has_credentials() {
+        return Err("o servidor TAXII exige TAXII_USERNAME/TAXII_PASSWORD ou TAXII_API_KEYS".into());
+    }
+
+    let listener = tokio::net::TcpListener::bind(&config.bind).await?;
+    info!("🛰️ Servidor TAXII 2.1 ouvindo em http://{}/taxii2/", listener.local_addr()?);
+
+    axum::serve(listener, router(database, config)).await?;
+    Ok(())
+}
+
+// Basic auth ou chave de API (Authorization: Bearer <chave> ou X-API-Key)
+pub fn is_authorized(config: &TaxiiConfig, headers: &HeaderMap) -> bool {
+    if let Some(key) = headers.get("x-api-key").and_then(|value| value.to_str().ok()) {
+        return config.api_keys.iter().any(|configured| configured == key);
+    }
+
+    let Some(authorization) = headers.get(header::AUTHORIZATION).and_then(|value| value.to_str().ok()) else {
+        return false;
+    };
+    if let Some(key) = authorization.strip_prefix("Bearer ") {
+        return config.api_keys.iter().any(|configured| configured == key.trim());
+    }
+    if let (Some(encoded), Some(username), Some(password)) = (authorization.strip_prefix("Basic "), &config.username, &config.password) {
+        let decoded = STANDARD.decode(encoded.trim()).ok().and_then(|bytes| String::from_utf8(bytes).ok());
+        return decoded.as_deref() == Some(format!("{}:{}", username, password).as_str());
+    }
+
+    false
+}
+
+async fn require_auth(State(state): State<TaxiiState>, request: Request, next: Next) -> Response {
+    if is_authorized(&state.config, request.headers()) {
+        return next.run(request).await;
+    }
+
+    let mut response = TaxiiError::new(StatusCode::UNAUTHORIZED, "Não autorizado", "credenciais ausentes ou inválidas").into_response();
+    response.headers_mut().insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Basic realm=\"f1000\""));
+    response
+}
+
+async fn discovery(State(state): State<TaxiiState>, headers: HeaderMap) -> Response {
+    let base = match &state.config.public_url {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None => {
+            let host = headers.get(header::HOST).and_then(|value| value.to_str().ok()).unwrap_or("localhost");
+            format!("http://{}", host)
+        },
+    };
+    let api_root = format!("{}{}", base, API_ROOT_PATH);
+
+    taxii_json(json!({
+        "title": "F1000",
+        "description": "Indicadores extraídos das mensagens coletadas no Telegram",
+        "default": api_root,
+        "api_roots": [api_root],
+    }))
+}
+
+async fn api_root() -> Response {
+    taxii_json(json!({
+        "title": "F1000 Telegram",
+        "description": "Uma coleção por grupo monitorado e a coleção \"all\" com todos os indicadores",
+        "versions": [TAXII_MEDIA_TYPE],
+        "max_content_length": MAX_CONTENT_LENGTH,
+    }))
+}
+
+async fn collections(State(state): State<TaxiiState>) -> Result<Response, TaxiiError> {
+    let groups = TelegramGroup::find_all(state.database.get_pool()).await?;
+
+    let mut collections = vec![collection_object(None)];
+    collections.extend(groups.iter().map(|group| collection_object(Some(group))));
+
+    Ok(taxii_json(json!({ "collections": collections })))
+}
+
+async fn collection(State(state): State<TaxiiState>, Path(id): Path<String>) -> Result<Response, TaxiiError> {
+    let group = resolve_collection(&state.database, &id).await?;
+    Ok(taxii_json(collection_object(group.as_ref())))
+}
+
+async fn objects(
+    State(state): State<TaxiiState>,
+    Path(id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, TaxiiError> {
+    let group = resolve_collection(&state.database, &id).await?;
+    let mut query = objects_query(&params, state.config.page_size)?;
+    query.group_id = group.map(|group| group.id);
+
+    // Um item a mais indica se há próxima página
+    let page_size = query.limit;
+    query.limit += 1;
+    let mut found = CollectedIoc::find(state.database.get_pool(), &query).await?;
+    let more = found.len() as i64 > page_size;
+    found.truncate(page_size as usize);
+
+    Ok(envelope(&found, more))
+}
+
+pub fn collection_id(group: Option<&TelegramGroup>) -> Uuid {
+    match group {
+        Some(group) => group.id,
+        None => Uuid::new_v5(&Uuid::NAMESPACE_URL, b"f1000:taxii:all"),
+    }
+}
+
+pub fn collection_object(group: Option<&TelegramGroup>) -> Value {
+    let mut object = json!({
+        "id": collection_id(group),
+        "can_read": true,
+        "can_write": false,
+        "media_types": [STIX_MEDIA_TYPE],
+    });
+
+    match group {
+        Some(group) => {
+            object["title"] = json!(group.title.clone()
+                .or_else(|| group.username.as_ref().map(|username| format!("@{}", username)))
+                .unwrap_or_else(|| group.telegram_chat_id.to_string()));
+            object["description"] = json!(format!("Indicadores vistos no chat {} ({})", group.telegram_chat_id, group.chat_type));
+        },
+        None => {
+            object["title"] = json!("Todos os grupos");
+            object["description"] = json!("Indicadores vistos em qualquer chat monitorado");
+            object["alias"] = json!(ALL_COLLECTION_ALIAS);
+        },
+    }
+    object
+}
+
+// None é a coleção "all"; aceita o alias ou o UUID de qualquer coleção
+async fn resolve_collection(database: &Database, id: &str) -> Result<Option<TelegramGroup>, TaxiiError> {
+    let not_found = || TaxiiError::new(StatusCode::NOT_FOUND, "Coleção não encontrada", &format!("coleção desconhecida: {}", id));
+
+    if id == ALL_COLLECTION_ALIAS {
+        return Ok(None);
+    }
+    let id = Uuid::parse_str(id).map_err(|_| not_found())?;
+    if id == collection_id(None) {
+        return Ok(None);
+    }
+
+    TelegramGroup::find_by_ids(database.get_pool(), &[id]).await?
+        .into_iter()
+        .next()
+        .map(Some)
+        .ok_or_else(not_found)
+}
+
+// Traduz added_after, limit, next, match[id] e match[type] para a consulta no banco
+pub fn objects_query(params: &HashMap<String, String>, page_size: i64) -> Result<CollectedIocQuery, TaxiiError> {
+    let bad_request = |description: String| TaxiiError::new(StatusCode::BAD_REQUEST, "Parâmetro inválido", &description);
+    let mut query = CollectedIocQuery { limit: page_size, ..Default::default() };
+
+    if let Some(limit) = params.get("limit") {
+        let limit: i64 = limit.parse().map_err(|_| bad_request(format!("limit inválido: {}", limit)))?;
+        query.limit = limit.clamp(1, page_size);
+    }
+
+    if let Some(added_after) = params.get("added_after") {
+        let date = DateTime::parse_from_rfc3339(added_after).map_err(|_| bad_request(format!("added_after inválido: {}", added_after)))?;
+        query.added_after = Some(date.with_timezone(&Utc));
+    }
+
+    // O cursor de next é mais preciso que added_after e prevalece sobre ele
+    if let Some(next) = params.get("next") {
+        let cursor = Cursor::decode(next).ok_or_else(|| bad_request(format!("next inválido: {}", next)))?;
+        query.added_after = Some(cursor.date);
+        query.after_id = Some(cursor.id);
+    }
+
+    if let Some(ids) = params.get("match[id]") {
+        query.ids = Some(ids.split(',')
+            .filter_map(|id| id.trim().split_once("--"))
+            .filter(|(object_type, _)| matches!(*object_type, "indicator" | "vulnerability"))
+            .filter_map(|(_, uuid)| Uuid::parse_str(uuid).ok())
+            .collect());
+    }
+
+    // CVEs saem como vulnerability, todo o resto como indicator
+    if let Some(types) = params.get("match[type]") {
+        let types: Vec<&str> = types.split(',').map(str::trim).collect();
+        match (types.contains(&"indicator"), types.contains(&"vulnerability")) {
+            (true, true) => {},
+            (true, false) => query.exclude_ioc_types = Some(vec!["cve".to_string()]),
+            (false, true) => query.ioc_types = Some(vec!["cve".to_string()]),
+            (false, false) => query.ioc_types = Some(Vec::new()),
+        }
+    }
+
+    Ok(query)
+}
+
+pub fn envelope(found: &[CollectedIoc], more: bool) -> Response {
+    let producer_id = stix::producer_id();
+    let mut body = json!({ "more": more });
+    if !found.is_empty() {
+        body["objects"] = json!(found.iter().map(|collected| stix::ioc_object(&collected.ioc, &producer_id)).collect::<Vec<_>>());
+    }
+    if let (true, Some(last)) = (more, found.last()) {
+        body["next"] = json!(Cursor::new(last.date_added, last.ioc.id).encode());
+    }
+
+    let mut response = taxii_json(body);
+    if let (Some(first), Some(last)) = (found.first(), found.last()) {
+        for (name, date) in [("x-taxii-date-added-first", first.date_added), ("x-taxii-date-added-last", last.date_added)] {
+            if let Ok(value) = HeaderValue::from_str(&date_added(date)) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+    }
+    response
+}
+
+// Microssegundos: o cliente reenvia esse valor em added_after sem perder objetos do mesmo milissegundo
+fn date_added(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+}
+
+fn taxii_json(body: Value) -> Response {
+    ([(header::CONTENT_TYPE, TAXII_MEDIA_TYPE)], body.to_string()).into_response()
+}
+
+#[derive(Debug)]
+pub struct TaxiiError {
+    status: StatusCode,
+    title: String,
+    description: String,
+}
+
+impl TaxiiError {
+    fn new(status: StatusCode, title: &str, description: &str) -> Self {
+        Self { status, title: title.to_string(), description: description.to_string() }
+    }
+}
+
+impl From<sqlx::Error> for TaxiiError {
+    fn from(e: sqlx::Error) -> Self {
+        warn!("❌ Erro no banco ao atender requisição TAXII: {}", e);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "Erro interno", "falha ao consultar o banco de dados")
+    }
+}
+
+impl IntoResponse for TaxiiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "title": self.title,
+            "description": self.description,
+            "http_status": self.status.as_u16().to_string(),
+        });
+        (self.status, [(header::CONTENT_TYPE, TAXII_MEDIA_TYPE)], body.to_string()).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests;
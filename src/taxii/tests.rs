@@ -0,0 +1,155 @@
+use super::*;
+use crate::db::test_support::{create_group, minimal_message, test_database};
+use crate::db::{Ioc, NewTelegramMessage, TelegramMessage};
+use crate::test_support::serve;
+use chrono::TimeZone;
+
+fn config() -> TaxiiConfig {
+    TaxiiConfig {
+        bind: "127.0.0.1:0".to_string(),
+        public_url: None,
+        username: Some("analista".to_string()),
+        password: Some("segredo".to_string()),
+        api_keys: vec!["chave".to_string()],
+        page_size: 100,
+    }
+}
+
+fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.insert(*name, HeaderValue::from_str(value).unwrap());
+    }
+    headers
+}
+
+fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+#[test]
+fn accepts_basic_auth_and_api_keys() {
+    let config = config();
+    let basic = format!("Basic {}", STANDARD.encode("analista:segredo"));
+
+    assert!(is_authorized(&config, &headers(&[("authorization", &basic)])));
+    assert!(is_authorized(&config, &headers(&[("authorization", "Bearer chave")])));
+    assert!(is_authorized(&config, &headers(&[("x-api-key", "chave")])));
+
+    assert!(!is_authorized(&config, &headers(&[])));
+    assert!(!is_authorized(&config, &headers(&[("authorization", &format!("Basic {}", STANDARD.encode("analista:errada")))])));
+    assert!(!is_authorized(&config, &headers(&[("x-api-key", "outra")])));
+}
+
+#[test]
+fn cursor_round_trips_with_microseconds() {
+    let date = Utc.timestamp_micros(1_714_564_800_123_456).unwrap();
+    let id = Uuid::new_v4();
+
+    assert_eq!(Cursor::decode(&Cursor::new(date, id).encode()), Some(Cursor::new(date, id)));
+    assert_eq!(Cursor::decode("não-é-cursor"), None);
+}
+
+#[test]
+fn objects_query_translates_filters() {
+    let query = objects_query(&params(&[
+        ("added_after", "2024-05-01T00:00:00Z"),
+        ("limit", "5000"),
+        ("match[type]", "vulnerability"),
+    ]), 100).unwrap();
+    assert_eq!(query.added_after, Some(Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap()));
+    assert_eq!(query.limit, 100);
+    assert_eq!(query.ioc_types, Some(vec!["cve".to_string()]));
+
+    let id = Uuid::new_v4();
+    let query = objects_query(&params(&[("match[type]", "indicator"), ("match[id]", &format!("indicator--{},identity--{}", id, id))]), 100).unwrap();
+    assert_eq!(query.exclude_ioc_types, Some(vec!["cve".to_string()]));
+    assert_eq!(query.ids, Some(vec![id]));
+
+    assert!(objects_query(&params(&[("added_after", "ontem")]), 100).is_err());
+    assert!(objects_query(&params(&[("next", "???")]), 100).is_err());
+}
+
+#[test]
+fn all_collection_has_a_fixed_id_and_alias() {
+    let all = collection_object(None);
+
+    assert_eq!(all["id"], collection_id(None).to_string());
+    assert_eq!(all["alias"], ALL_COLLECTION_ALIAS);
+    assert_eq!(all["media_types"][0], STIX_MEDIA_TYPE);
+}
+
+// Grupo com três IOCs, um deles CVE
+async fn seed_group(database: &Database) -> TelegramGroup {
+    let pool = database.get_pool();
+    let group = create_group(pool, "channel").await;
+    let message = TelegramMessage::create(pool, NewTelegramMessage {
+        message_type: "text".to_string(),
+        date: Utc::now(),
+        ..minimal_message(group.id)
+    })
+    .await
+    .unwrap();
+
+    let suffix = Uuid::new_v4().simple().to_string();
+    for (ioc_type, value) in [("domain", format!("{}.example", suffix)), ("ipv4", "203.0.113.7".to_string()), ("cve", "CVE-2024-3094".to_string())] {
+        Ioc::record_sighting(pool, message.id, ioc_type, &value, Utc::now()).await.unwrap();
+    }
+
+    group
+}
+
+async fn spawn_server(database: Database) -> String {
+    format!("http://{}", serve(router(database, config())).await)
+}
+
+async fn get(url: &str) -> (StatusCode, HeaderMap, Value) {
+    let response = reqwest::Client::new().get(url).header("x-api-key", "chave").send().await.unwrap();
+    let status = StatusCode::from_u16(response.status().as_u16()).unwrap();
+    let mut headers = HeaderMap::new();
+    for (name, value) in response.headers() {
+        headers.insert(name.as_str().parse::<header::HeaderName>().unwrap(), HeaderValue::from_bytes(value.as_bytes()).unwrap());
+    }
+    (status, headers, response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn serves_group_collections_with_pagination() {
+    let database = test_database().await;
+    let group = seed_group(&database).await;
+    let base = spawn_server(database).await;
+
+    let unauthorized = reqwest::get(format!("{}/taxii2/", base)).await.unwrap();
+    assert_eq!(unauthorized.status().as_u16(), 401);
+    assert!(unauthorized.headers().contains_key("www-authenticate"));
+
+    let (_, headers, discovery) = get(&format!("{}/taxii2/", base)).await;
+    assert_eq!(headers[header::CONTENT_TYPE], TAXII_MEDIA_TYPE);
+    assert_eq!(discovery["default"], format!("{}/f1000/", base));
+
+    let (_, _, collections) = get(&format!("{}/f1000/collections/", base)).await;
+    let ids: Vec<&str> = collections["collections"].as_array().unwrap().iter().map(|collection| collection["id"].as_str().unwrap()).collect();
+    assert!(ids.contains(&collection_id(None).to_string().as_str()));
+    assert!(ids.contains(&group.id.to_string().as_str()));
+
+    let objects_url = format!("{}/f1000/collections/{}/objects/", base, group.id);
+    let (_, headers, first) = get(&format!("{}?limit=2", objects_url)).await;
+    assert_eq!(first["more"], true);
+    assert_eq!(first["objects"].as_array().unwrap().len(), 2);
+    assert!(headers.contains_key("x-taxii-date-added-last"));
+
+    let (_, _, second) = get(&format!("{}?limit=2&next={}", objects_url, first["next"].as_str().unwrap())).await;
+    assert_eq!(second["more"], false);
+    assert_eq!(second["objects"].as_array().unwrap().len(), 1);
+
+    let (_, _, vulnerabilities) = get(&format!("{}?match[type]=vulnerability", objects_url)).await;
+    assert_eq!(vulnerabilities["objects"][0]["type"], "vulnerability");
+    assert_eq!(vulnerabilities["objects"].as_array().unwrap().len(), 1);
+
+    let (_, _, all) = get(&format!("{}/f1000/collections/all/", base)).await;
+    assert_eq!(all["alias"], ALL_COLLECTION_ALIAS);
+
+    let (status, _, error) = get(&format!("{}/f1000/collections/{}/objects/", base, Uuid::new_v4())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error["http_status"], "404");
+}
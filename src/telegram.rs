@@ -1,17 +1,40 @@
-use grammers_client::{Client, Config};
+use grammers_client::{Client, Config, InitParams, InvocationError};
+use grammers_client::types::Chat;
 use grammers_session::Session;
 use tracing::{info, warn};
-use std::io::{self, Write};
-use grammers_client::SignInError;
-use crate::db::{Database, NewTelegramUser, NewTelegramGroup, NewTelegramMessage, TelegramUser, TelegramGroup, TelegramMessage};
-use grammers_client::types::{Message, Chat};
-use chrono::Utc;
+use std::sync::Arc;
+use crate::cli::JoinTarget;
+use crate::config::LoginMethod;
+use crate::db::Database;
+use grammers_tl_types as tl;
+use crate::shutdown::Shutdown;
+
+mod backfill;
+pub mod claims;
+pub mod entities;
+mod gap_recovery;
+pub mod login;
+mod message_processor;
+pub mod supervisor;
+
+pub use backfill::Backfill;
+pub use claims::ChatClaims;
+pub use gap_recovery::GapRecovery;
+pub use login::{CredentialProvider, LoginError};
+pub use message_processor::MessageProcessor;
+pub use supervisor::{ConnectionHealth, ConnectionState, SessionRevoked};
 
 pub struct TelegramClient {
     api_id: i32,
     api_hash: String,
     phone_number: String,
     session_path: String,
+    reconnect_base_delay: std::time::Duration,
+    reconnect_max_delay: std::time::Duration,
+    health: ConnectionHealth,
+    login_method: LoginMethod,
+    credentials: Arc<dyn CredentialProvider>,
+    login_attempts: u32,
 }
 
 impl TelegramClient {
@@ -21,27 +44,60 @@ impl TelegramClient {
             api_hash,
             phone_number,
             session_path,
+            reconnect_base_delay: supervisor::DEFAULT_RECONNECT_BASE_DELAY,
+            reconnect_max_delay: supervisor::DEFAULT_RECONNECT_MAX_DELAY,
+            health: ConnectionHealth::new(),
+            login_method: LoginMethod::Code,
+            credentials: Arc::new(login::StdinProvider),
+            login_attempts: 3,
         }
     }
 
+    pub fn with_reconnect_delays(mut self, base: std::time::Duration, max: std::time::Duration) -> Self {
+        self.reconnect_base_delay = base;
+        self.reconnect_max_delay = max.max(base);
+        self
+    }
+
+    pub fn with_login(mut self, method: LoginMethod, credentials: Arc<dyn CredentialProvider>, attempts: u32) -> Self {
+        self.login_method = method;
+        self.credentials = credentials;
+        self.login_attempts = attempts.max(1);
+        self
+    }
+
+    pub fn health(&self) -> ConnectionHealth {
+        self.health.clone()
+    }
+
     pub async fn connect(&self) -> Result<Client, Box<dyn std::error::Error>> {
         info!("Conectando ao Telegram...");
         
         let session = self.load_or_create_session()?;
         
-        let config = Config {
-            api_id: self.api_id,
-            api_hash: self.api_hash.clone(),
-            session,
-            params: Default::default(),
-        };
-        
-        let client = Client::connect(config).await?;
+        self.health.set(ConnectionState::Connecting);
+        let client = Client::connect(self.client_config(session)).await?;
         
         info!("Conectado ao Telegram com sucesso!");
         Ok(client)
     }
     
+    fn client_config(&self, session: Session) -> Config {
+        Config {
+            api_id: self.api_id,
+            api_hash: self.api_hash.clone(),
+            session,
+            params: InitParams {
+                reconnection_policy: supervisor::JitteredReconnect::new(
+                    self.reconnect_base_delay,
+                    self.reconnect_max_delay,
+                    self.health.clone(),
+                ).leak(),
+                ..Default::default()
+            },
+        }
+    }
+    
     pub async fn save_session(&self, client: &Client) -> Result<(), Box<dyn std::error::Error>> {
         let path = std::path::PathBuf::from(&self.session_path);
         info!("💾 Salvando sessão em: {}", path.display());
@@ -126,7 +182,7 @@ impl TelegramClient {
         Ok(session)
     }
 
-    pub async fn sign_in(&self, client: &mut Client) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn sign_in(&self, client: &mut Client) -> Result<(), LoginError> {
         info!("Iniciando processo de login...");
         
         if client.is_authorized().await? {
@@ -134,275 +190,158 @@ impl TelegramClient {
             return Ok(());
         }
         
-        let login_token = client.request_login_code(&self.phone_number).await?;
-        info!("Código enviado para {}", self.phone_number);
-        
-        println!("\n🔐 Digite o código de verificação que você recebeu no SMS:");
-        print!("Código: ");
-        io::stdout().flush()?;
-        
-        let mut code = String::new();
-        io::stdin().read_line(&mut code)?;
-        let code = code.trim();
-        
-        info!("Tentando fazer login com o código...");
+        // Sem quem responda, nem pede o código: o Telegram limita quantos podem ser enviados
+        if !self.credentials.is_available() {
+            return Err(LoginError::Unavailable(format!(
+                "o provedor {} não pode responder aqui: rode `f1000 login` num terminal ou defina TELEGRAM_CREDENTIALS",
+                self.credentials.name(),
+            )));
+        }
         
-        match client.sign_in(&login_token, code).await { 
-            Err(SignInError::PasswordRequired(password_token) ) => {
-                println!("\n🔐 Digite sua senha 2FA:");
-                print!("Senha: ");
-                io::stdout().flush()?;
-                
-                let mut password = String::new();
-                io::stdin().read_line(&mut password)?;
-                let password = password.trim();
+        let result = match self.login_method {
+            LoginMethod::Code => login::sign_in_with_code(client, &self.phone_number, self.credentials.as_ref(), self.login_attempts).await,
+            LoginMethod::Qr => login::sign_in_with_qr(client, self.api_id, &self.api_hash, |session| self.client_config(session), self.credentials.as_ref(), self.login_attempts).await,
+        };
         
-                client
-                    .check_password(password_token, password)
-                    .await.unwrap();
-                Ok(())
-            }
-            Ok(_) => {
-                info!("✅ Login realizado com sucesso!");
-                Ok(())
-            },
-            Err(e) => {
-                warn!("❌ Erro no login: {}", e);
-                Err(e.into())
-            }
+        match &result {
+            Ok(()) => info!("✅ Login realizado com sucesso!"),
+            Err(e) => warn!("❌ Erro no login: {}", e),
         }
+        result
     }
 
 
-    pub async fn start_listening(&self, client: &mut Client, database: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn start_listening(&self, client: &mut Client, database: &Database, processor: &MessageProcessor, shutdown: &Shutdown) -> Result<(), Box<dyn std::error::Error>> {
+        let gap_recovery = GapRecovery::new(database.clone(), processor.clone()).with_shutdown(shutdown.clone());
+        if let Err(e) = gap_recovery.run(client).await {
+            warn!("⚠️ Erro na recuperação de mensagens perdidas: {}", e);
+        }
+        
         info!("🔄 Iniciando coleta de mensagens em tempo real...");
         info!("📱 Aguardando mensagens... (Ctrl+C para parar)");
         
-        loop {
-            let update_result = tokio::time::timeout(
-                tokio::time::Duration::from_secs(10),
-                client.next_update()
-            ).await;
+        let mut supervisor = supervisor::Supervisor::new(self.reconnect_base_delay, self.reconnect_max_delay, self.health.clone());
+        supervisor.connected();
+        let heartbeat = supervisor::spawn_heartbeat(client.clone(), self.health.clone());
+        
+        let result: Result<(), SessionRevoked> = loop {
+            // A mensagem em processamento termina antes do encerramento: a espera só é interrompida entre updates
+            let update_result = tokio::select! {
+                _ = shutdown.requested() => break Ok(()),
+                reason = self.health.unauthorized() => break Err(SessionRevoked { reason }),
+                update_result = client.next_update() => update_result,
+            };
             
             match update_result {
-                Ok(Ok(update)) => {
-                    if let grammers_client::Update::NewMessage(message) = update {
-                        if let Err(e) = self.save_message_simple(message, database).await {
-                            warn!("❌ Erro ao salvar mensagem: {}", e);
-                        }
+                Ok(update) => {
+                    supervisor.connected();
+                    match update {
+                        grammers_client::Update::NewMessage(message) => {
+                            if let Err(e) = processor.process_message(&message).await {
+                                warn!("❌ Erro ao salvar mensagem: {}", e);
+                            }
+                        },
+                        grammers_client::Update::MessageEdited(message) => {
+                            if let Err(e) = processor.process_edit(&message).await {
+                                warn!("❌ Erro ao salvar edição: {}", e);
+                            }
+                        },
+                        grammers_client::Update::MessageDeleted(deletion) => {
+                            if let Err(e) = processor.process_deletion(&deletion).await {
+                                warn!("❌ Erro ao registrar exclusão: {}", e);
+                            }
+                        },
+                        _ => {}
                     }
                 },
-                Ok(Err(e)) => {
-                    warn!("❌ Erro ao receber update: {}", e);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                },
-                Err(_) => {
-                }
-            }
-        }
-    }
-    
-    async fn save_message_simple(&self, message: Message, database: &Database) -> Result<(), Box<dyn std::error::Error>> {
-        let pool = database.get_pool();
-        
-        let user_id = match self.get_message_sender_info(&message).await {
-            Ok((telegram_user_id, first_name, last_name, username, phone, is_bot, is_verified, is_premium, language_code)) => {
-                match self.process_user_from_info(telegram_user_id, first_name, last_name, username, phone, is_bot, is_verified, is_premium, language_code, pool).await {
-                    Ok(user) => {
-                        info!("👤 Usuário processado: {} (ID: {})", 
-                            user.first_name.as_deref().unwrap_or("Sem nome"), 
-                            user.telegram_user_id);
-                        Some(user.id)
-                    },
-                    Err(e) => {
-                        warn!("❌ Erro ao processar usuário: {}", e);
-                        None
+                Err(e) => {
+                    let recovered = tokio::select! {
+                        _ = shutdown.requested() => break Ok(()),
+                        recovered = supervisor.recover(&e) => recovered,
+                    };
+                    if let Err(revoked) = recovered {
+                        break Err(revoked);
                     }
-                }
-            },
-            Err(_) => None,
-        };
-        
-        let group_id = {
-            let chat = message.chat();
-            match self.process_chat_simple(chat, pool).await {
-                Ok(group) => {
-                    info!("👥 Grupo processado: {} (ID: {})", 
-                        group.title.as_deref().unwrap_or("Sem título"), 
-                        group.telegram_chat_id);
-                    Some(group.id)
                 },
-                Err(e) => {
-                    warn!("❌ Erro ao processar grupo: {}", e);
-                    None
-                }
             }
         };
         
-        let message_text = message.text();
-        let message_type = if !message_text.is_empty() { "text" } else { "unknown" };
-        
-        let new_message = NewTelegramMessage {
-            telegram_message_id: message.id() as i64,
-            user_id,
-            group_id,
-            message_text: if !message_text.is_empty() { Some(message_text.to_string()) } else { None },
-            message_type: message_type.to_string(),
-            date: Utc::now(),
-            edit_date: None,
-            forward_from_user_id: None,
-            forward_from_group_id: None,
-            forward_date: None,
-            reply_to_message_id: None,
-            media_file_id: None,
-            media_file_unique_id: None,
-            media_file_size: None,
-            media_mime_type: None,
-            media_file_name: None,
-            location_latitude: None,
-            location_longitude: None,
-            contact_phone_number: None,
-            contact_first_name: None,
-            contact_last_name: None,
-        };
-        
-        match TelegramMessage::create(pool, new_message).await {
-            Ok(_) => {
-                info!("💾 Mensagem salva: ID {} (tipo: {})", 
-                    message.id(), message_type);
-            },
-            Err(e) => {
-                if e.to_string().contains("duplicate key") {
-                    info!("🔄 Mensagem duplicada ignorada: ID {}", message.id());
-                } else {
-                    warn!("❌ Erro ao salvar mensagem {}: {}", message.id(), e);
-                }
-            }
+        heartbeat.abort();
+        if result.is_ok() {
+            info!("⏹️ Coleta em tempo real interrompida");
         }
-        
-        Ok(())
+        result.map_err(Into::into)
     }
-    
+}
 
-    
-    async fn process_chat_simple(&self, chat: Chat, pool: &sqlx::PgPool) -> Result<TelegramGroup, Box<dyn std::error::Error>> {
-        if let Some(existing_group) = TelegramGroup::find_by_telegram_id(pool, chat.id()).await? {
-            return Ok(existing_group);
-        }
-        
-        let chat_type = match chat {
-            Chat::User(_) => "private",
-            Chat::Group(_) => "group",
-            Chat::Channel(_) => "channel",
-        }.to_string();
-        
-        let (title, username, description) = match chat {
-            Chat::User(ref user) => (
-                Some(format!("{} {}", 
-                    user.first_name(), 
-                    user.last_name().unwrap_or("")
-                ).trim().to_string()),
-                user.username().map(|s| s.to_string()),
-                None
-            ),
-            Chat::Group(ref group) => (
-                Some(group.title().to_string()),
-                None,
-                None
-            ),
-            Chat::Channel(ref channel) => (
-                Some(channel.title().to_string()),
-                channel.username().map(|s| s.to_string()),
-                None
-            ),
-        };
-        
-        let new_group = NewTelegramGroup {
-            telegram_chat_id: chat.id(),
-            chat_type,
-            title,
-            username,
-            description,
-            invite_link: None,
-            member_count: None,
-            is_verified: false,
-            is_restricted: false,
-            is_scam: false,
-            is_fake: false,
-        };
-        
-        TelegramGroup::create(pool, new_group).await
-            .map_err(|e| format!("Erro ao criar grupo: {}", e).into())
+pub(crate) fn flood_wait(error: &InvocationError) -> Option<std::time::Duration> {
+    match error {
+        InvocationError::Rpc(rpc) if rpc.is("FLOOD_WAIT") || rpc.is("FLOOD_PREMIUM_WAIT") => {
+            Some(std::time::Duration::from_secs(rpc.value.unwrap_or(1) as u64))
+        },
+        _ => None,
     }
-    
-    async fn get_message_sender_info(&self, message: &Message) -> Result<(i64, Option<String>, Option<String>, Option<String>, Option<String>, bool, bool, bool, Option<String>), Box<dyn std::error::Error>> {
-        if let Some(sender) = message.sender() {
-            let user_id = sender.id();
-            
-            let (first_name, last_name, username, phone, is_bot, is_verified, is_premium, language_code) = match sender {
-                Chat::User(user) => (
-                    Some(user.first_name().to_string()),
-                    user.last_name().map(|s| s.to_string()),
-                    user.username().map(|s| s.to_string()),
-                    user.phone().map(|s| s.to_string()),
-                    user.is_bot(),
-                    user.verified(),
-                    user.raw.premium,
-                    user.lang_code().map(|s| s.to_string())
-                ),
-                _ => (
-                    None,
-                    None,
-                    None,
-                    None,
-                    false,
-                    false,
-                    false,
-                    None
-                )
-            };
-            
-            Ok((user_id, first_name, last_name, username, phone, is_bot, is_verified, is_premium, language_code))
-        } else {
-            Err("Mensagem sem remetente".into())
-        }
+}
+
+// Mesmos valores gravados em telegram_groups.chat_type
+pub fn chat_type(chat: &Chat) -> &'static str {
+    match chat {
+        Chat::User(_) => "private",
+        Chat::Group(group) if group.is_megagroup() => "supergroup",
+        Chat::Group(_) => "group",
+        Chat::Channel(_) => "channel",
     }
+}
+
+// Canais e supergrupos numeram as mensagens no próprio chat; chats privados e grupos básicos, por conta
+pub fn is_shared_chat(chat_type: &str) -> bool {
+    matches!(chat_type, "channel" | "supergroup")
+}
+
+pub async fn list_dialog_chats(client: &Client) -> Result<Vec<Chat>, InvocationError> {
+    let mut dialogs = client.iter_dialogs();
+    let mut chats = Vec::new();
     
-    async fn process_user_from_info(
-        &self, 
-        telegram_user_id: i64, 
-        first_name: Option<String>, 
-        last_name: Option<String>, 
-        username: Option<String>, 
-        phone: Option<String>,
-        is_bot: bool,
-        is_verified: bool,
-        is_premium: bool,
-        language_code: Option<String>,
-        pool: &sqlx::PgPool
-    ) -> Result<TelegramUser, Box<dyn std::error::Error>> {
-        if let Some(existing_user) = TelegramUser::find_by_telegram_id(pool, telegram_user_id).await? {
-            return Ok(existing_user);
+    loop {
+        match dialogs.next().await {
+            Ok(Some(dialog)) => chats.push(dialog.chat().clone()),
+            Ok(None) => return Ok(chats),
+            Err(e) => match flood_wait(&e) {
+                Some(delay) => {
+                    warn!("⏳ FLOOD_WAIT ao listar diálogos, aguardando {}s", delay.as_secs());
+                    tokio::time::sleep(delay).await;
+                },
+                None => return Err(e),
+            },
         }
-        
-        let new_user = NewTelegramUser {
-            telegram_user_id,
-            username,
-            first_name,
-            last_name,
-            phone_number: phone,
-            is_bot,
-            is_verified,
-            is_premium,
-            language_code,
-        };
-        
-        TelegramUser::create(pool, new_user).await
-            .map_err(|e| format!("Erro ao criar usuário: {}", e).into())
     }
+}
 
+// Grupos públicos pelo username, privados pelo hash do convite
+pub async fn join_chat(client: &Client, target: &JoinTarget) -> Result<Chat, Box<dyn std::error::Error>> {
+    let chat = match target {
+        JoinTarget::Username(username) => {
+            let chat = client.resolve_username(username).await?
+                .ok_or_else(|| format!("@{} não encontrado", username))?;
+            // Só supergrupos e canais têm username e aceitam JoinChannel
+            if !matches!(chat_type(&chat), "supergroup" | "channel") {
+                return Err(format!("@{} não é um grupo ou canal", username).into());
+            }
+            client.join_chat(chat.pack()).await?.unwrap_or(chat)
+        },
+        JoinTarget::InviteHash(hash) => {
+            let updates = client.invoke(&tl::functions::messages::ImportChatInvite { hash: hash.clone() }).await?;
+            let chats = match updates {
+                tl::enums::Updates::Updates(updates) => updates.chats,
+                tl::enums::Updates::Combined(updates) => updates.chats,
+                _ => Vec::new(),
+            };
+            chats.into_iter().next()
+                .map(Chat::from_raw)
+                .ok_or("o convite foi aceito, mas o Telegram não informou o chat")?
+        },
+    };
     
-
+    info!("🚪 Entrou em {} ({})", chat.name(), chat.id());
+    Ok(chat)
 }
-
-
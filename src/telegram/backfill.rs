@@ -0,0 +1,154 @@
+use crate::config::BackfillConfig;
+use crate::db::{BackfillState, Database};
+use crate::policy::CaptureMode;
+use crate::shutdown::Shutdown;
+use super::{flood_wait, list_dialog_chats, MessageProcessor};
+use grammers_client::Client;
+use grammers_client::types::{Chat, Message};
+use tracing::{info, warn};
+
+// Quantas mensagens processar entre cada gravação do cursor
+const CURSOR_SAVE_INTERVAL: i64 = 100;
+
+pub struct Backfill {
+    database: Database,
+    processor: MessageProcessor,
+    config: BackfillConfig,
+    shutdown: Shutdown,
+    full_only: bool,
+}
+
+impl Backfill {
+    pub fn new(database: Database, processor: MessageProcessor, config: BackfillConfig) -> Self {
+        Self {
+            database,
+            processor,
+            config,
+            shutdown: Shutdown::new(),
+            full_only: false,
+        }
+    }
+
+    // Backfill automático da coleta: só os chats cuja política é full
+    pub fn full_only(mut self) -> Self {
+        self.full_only = true;
+        self
+    }
+
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    pub async fn run(&self, client: &Client) -> Result<(), Box<dyn std::error::Error>> {
+        info!("⏪ Iniciando backfill do histórico...");
+
+        let mut chats = Vec::new();
+        for chat in list_dialog_chats(client).await? {
+            let mode = self.processor.capture_mode(&chat).await?;
+            if (self.full_only && mode.backfills()) || (!self.full_only && mode != CaptureMode::Ignore) {
+                chats.push(chat);
+            }
+        }
+        info!("📚 {} chats encontrados para backfill", chats.len());
+
+        for chat in chats {
+            if self.shutdown.is_requested() {
+                info!("⏹️ Backfill interrompido; será retomado do último cursor salvo");
+                return Ok(());
+            }
+            if let Err(e) = self.backfill_chat(client, &chat).await {
+                warn!("❌ Erro no backfill do chat {} ({}): {}", chat.name(), chat.id(), e);
+            }
+        }
+
+        info!("✅ Backfill concluído");
+        Ok(())
+    }
+
+    async fn backfill_chat(&self, client: &Client, chat: &Chat) -> Result<(), Box<dyn std::error::Error>> {
+        let pool = self.database.get_pool();
+        let group = self.processor.process_chat(chat.clone()).await?;
+
+        let mut state = match BackfillState::find_by_group_id(pool, group.id).await? {
+            Some(state) if state.completed => {
+                info!("⏭️ Histórico de {} já coletado por completo", chat.name());
+                return Ok(());
+            },
+            Some(state) => {
+                info!("🔁 Retomando backfill de {} a partir da mensagem {:?}", chat.name(), state.oldest_message_id);
+                state
+            },
+            None => BackfillState {
+                group_id: group.id,
+                oldest_message_id: None,
+                oldest_message_date: None,
+                messages_processed: 0,
+                completed: false,
+                updated_at: chrono::Utc::now(),
+            },
+        };
+
+        let mut messages = client.iter_messages(chat);
+        if let Some(oldest_message_id) = state.oldest_message_id {
+            messages = messages.offset_id(oldest_message_id as i32);
+        }
+
+        let result = loop {
+            if self.shutdown.is_requested() {
+                break Ok(());
+            }
+
+            let message = match messages.next().await {
+                Ok(Some(message)) => message,
+                Ok(None) => {
+                    state.completed = true;
+                    break Ok(());
+                },
+                Err(e) => match flood_wait(&e) {
+                    Some(delay) => {
+                        warn!("⏳ FLOOD_WAIT no backfill de {}, aguardando {}s", chat.name(), delay.as_secs());
+                        tokio::select! {
+                            _ = tokio::time::sleep(delay) => {},
+                            _ = self.shutdown.requested() => {},
+                        }
+                        continue;
+                    },
+                    None => break Err(e.into()),
+                },
+            };
+
+            if self.reached_limit(&message) {
+                info!("🏁 Limite de backfill atingido em {} (mensagem {})", chat.name(), message.id());
+                break Ok(());
+            }
+
+            if let Err(e) = self.processor.process_message(&message).await {
+                warn!("❌ Erro ao salvar mensagem {} do backfill: {}", message.id(), e);
+            }
+
+            state.oldest_message_id = Some(message.id() as i64);
+            state.oldest_message_date = Some(message.date());
+            state.messages_processed += 1;
+
+            if state.messages_processed % CURSOR_SAVE_INTERVAL == 0 {
+                BackfillState::save(pool, &state).await?;
+                info!("📥 {}: {} mensagens processadas", chat.name(), state.messages_processed);
+            }
+        };
+
+        BackfillState::save(pool, &state).await?;
+        info!("📦 Backfill de {}: {} mensagens processadas", chat.name(), state.messages_processed);
+
+        result
+    }
+
+    fn reached_limit(&self, message: &Message) -> bool {
+        let before_date = self.config.until_date
+            .is_some_and(|until_date| message.date() < until_date);
+        let before_id = self.config.until_message_id
+            .is_some_and(|until_message_id| (message.id() as i64) < until_message_id);
+
+        before_date || before_id
+    }
+}
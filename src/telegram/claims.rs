@@ -0,0 +1,67 @@
+use crate::config::AccountConfig;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// Divide os canais e supergrupos entre as contas do processo: cada um é coletado por uma única conta,
+// mesmo quando várias são membros dele. Como os IDs das mensagens são do próprio chat, a conta dona pode
+// mudar entre execuções sem duplicar nem perder mensagens.
+#[derive(Clone, Default)]
+pub struct ChatClaims {
+    assignments: Arc<Vec<(String, Vec<String>)>>,
+    owners: Arc<Mutex<HashMap<i64, String>>>,
+}
+
+impl ChatClaims {
+    pub fn new(accounts: &[AccountConfig]) -> Self {
+        Self {
+            assignments: Arc::new(accounts.iter().map(|account| (account.id.clone(), account.chats.clone())).collect()),
+            owners: Arc::default(),
+        }
+    }
+
+    // Um chat listado em CHATS pertence à primeira conta que o lista; os demais ficam com a primeira
+    // conta sem lista que o encontrar. Conta com lista própria não coleta nada fora dela.
+    pub fn claim(&self, account_id: &str, chat_id: i64, username: Option<&str>) -> bool {
+        let mut owners = self.owners.lock().unwrap();
+        if let Some(owner) = owners.get(&chat_id) {
+            return owner == account_id;
+        }
+
+        let assigned_to = self.assignments.iter()
+            .find(|(_, chats)| chats.iter().any(|chat| chat_matches(chat, chat_id, username)))
+            .map(|(id, _)| id.as_str());
+        let owns = match assigned_to {
+            Some(owner) => owner == account_id,
+            None => !self.assignments.iter().any(|(id, chats)| id == account_id && !chats.is_empty()),
+        };
+
+        if owns {
+            owners.insert(chat_id, account_id.to_string());
+        }
+        owns
+    }
+
+    // Chats privados e grupos básicos não são disputados: cada conta guarda os seus, respeitando a lista de CHATS
+    pub fn allows(&self, account_id: &str, chat_id: i64, username: Option<&str>) -> bool {
+        self.assignments.iter()
+            .find(|(id, _)| id == account_id)
+            .is_none_or(|(_, chats)| chats.is_empty() || chats.iter().any(|chat| chat_matches(chat, chat_id, username)))
+    }
+
+    // Quando a coleta de uma conta termina, os chats dela voltam a ficar livres para as outras contas
+    pub fn release(&self, account_id: &str) {
+        self.owners.lock().unwrap().retain(|_, owner| owner != account_id);
+    }
+
+    pub fn owner(&self, chat_id: i64) -> Option<String> {
+        self.owners.lock().unwrap().get(&chat_id).cloned()
+    }
+}
+
+fn chat_matches(chat: &str, chat_id: i64, username: Option<&str>) -> bool {
+    chat.parse::<i64>().is_ok_and(|id| id == chat_id)
+        || username.is_some_and(|username| username.eq_ignore_ascii_case(chat.trim_start_matches('@')))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,62 @@
+use super::*;
+
+fn account(id: &str, chats: &[&str]) -> AccountConfig {
+    AccountConfig {
+        id: id.to_string(),
+        api_id: 12345,
+        api_hash: "hash".to_string(),
+        phone_number: "+5511999999999".to_string(),
+        session_path: format!("session-{}.session", id),
+        chats: chats.iter().map(|chat| chat.to_string()).collect(),
+    }
+}
+
+#[test]
+fn overlapping_chats_are_collected_by_the_first_account_that_sees_them() {
+    let claims = ChatClaims::new(&[account("principal", &[]), account("burner1", &[])]);
+
+    assert!(claims.claim("burner1", -1001, None));
+    assert!(!claims.claim("principal", -1001, None));
+    assert!(claims.claim("burner1", -1001, None));
+    assert_eq!(claims.owner(-1001).as_deref(), Some("burner1"));
+    assert_eq!(claims.owner(-1002), None);
+}
+
+#[test]
+fn assigned_chats_go_to_their_account_regardless_of_order() {
+    let claims = ChatClaims::new(&[account("principal", &[]), account("burner1", &["@Forum_Exemplo", "-1003"])]);
+
+    assert!(!claims.claim("principal", -1002, Some("forum_exemplo")));
+    assert!(!claims.claim("principal", -1003, None));
+    assert_eq!(claims.owner(-1002), None);
+    assert!(claims.claim("burner1", -1002, Some("forum_exemplo")));
+    assert!(claims.claim("burner1", -1003, None));
+
+    // Conta com lista própria não coleta o que estiver fora dela
+    assert!(!claims.claim("burner1", -1004, Some("outro")));
+    assert!(claims.claim("principal", -1004, Some("outro")));
+}
+
+#[test]
+fn private_chats_are_never_taken_from_other_accounts() {
+    let claims = ChatClaims::new(&[account("principal", &[]), account("burner1", &["@forum_exemplo"])]);
+
+    assert!(claims.allows("principal", 42, Some("fulano")));
+    assert!(!claims.allows("burner1", 42, Some("fulano")));
+    assert!(claims.allows("burner1", 43, Some("Forum_Exemplo")));
+    assert_eq!(claims.owner(42), None);
+}
+
+#[test]
+fn released_chats_can_be_claimed_by_another_account() {
+    let claims = ChatClaims::new(&[account("principal", &[]), account("burner1", &[])]);
+
+    assert!(claims.claim("principal", -1001, None));
+    assert!(claims.claim("burner1", -1002, None));
+    assert!(!claims.claim("burner1", -1001, None));
+
+    claims.release("principal");
+    assert_eq!(claims.owner(-1001), None);
+    assert_eq!(claims.owner(-1002).as_deref(), Some("burner1"));
+    assert!(claims.claim("burner1", -1001, None));
+}
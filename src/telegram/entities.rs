@@ -0,0 +1,74 @@
+use grammers_tl_types as tl;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageEntityInfo {
+    pub entity_type: String,
+    pub offset: i32,
+    pub length: i32,
+    pub url: Option<String>,
+    pub user_id: Option<i64>,
+    pub language: Option<String>,
+}
+
+pub fn parse_entities(entities: &[tl::enums::MessageEntity]) -> Vec<MessageEntityInfo> {
+    use tl::enums::MessageEntity as E;
+
+    entities.iter().map(|entity| {
+        let mut info = MessageEntityInfo {
+            entity_type: String::new(),
+            offset: entity.offset(),
+            length: entity.length(),
+            url: None,
+            user_id: None,
+            language: None,
+        };
+
+        info.entity_type = match entity {
+            E::Unknown(_) => "unknown",
+            E::Mention(_) => "mention",
+            E::Hashtag(_) => "hashtag",
+            E::BotCommand(_) => "bot_command",
+            E::Url(_) => "url",
+            E::Email(_) => "email",
+            E::Bold(_) => "bold",
+            E::Italic(_) => "italic",
+            E::Code(_) => "code",
+            E::Pre(pre) => {
+                info.language = Some(pre.language.clone()).filter(|language| !language.is_empty());
+                "pre"
+            },
+            E::TextUrl(text_url) => {
+                info.url = Some(text_url.url.clone());
+                "text_url"
+            },
+            E::MentionName(mention) => {
+                info.user_id = Some(mention.user_id);
+                "mention_name"
+            },
+            E::InputMessageEntityMentionName(_) => "mention_name",
+            E::Phone(_) => "phone",
+            E::Cashtag(_) => "cashtag",
+            E::Underline(_) => "underline",
+            E::Strike(_) => "strike",
+            E::BankCard(_) => "bank_card",
+            E::Spoiler(_) => "spoiler",
+            E::CustomEmoji(_) => "custom_emoji",
+            E::Blockquote(_) => "blockquote",
+        }.to_string();
+
+        info
+    }).collect()
+}
+
+// Offsets e tamanhos das entidades são contados em unidades UTF-16, não em bytes
+pub fn entity_text(text: &str, offset: i32, length: i32) -> Option<String> {
+    let units: Vec<u16> = text.encode_utf16().collect();
+    let start = usize::try_from(offset).ok()?;
+    let end = start.checked_add(usize::try_from(length).ok()?)?;
+    let slice = units.get(start..end)?;
+    String::from_utf16(slice).ok()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,44 @@
+use super::*;
+
+#[test]
+fn entity_text_uses_utf16_offsets() {
+    let text = "🚨 payload em https://evil.com";
+    // O emoji ocupa duas unidades UTF-16
+    assert_eq!(entity_text(text, 14, 16).as_deref(), Some("https://evil.com"));
+    assert_eq!(entity_text(text, 0, 2).as_deref(), Some("🚨"));
+}
+
+#[test]
+fn entity_text_rejects_out_of_range_entities() {
+    assert_eq!(entity_text("curto", 3, 10), None);
+    assert_eq!(entity_text("curto", -1, 2), None);
+}
+
+#[test]
+fn parse_entities_keeps_hidden_urls_and_mentions() {
+    let entities = vec![
+        tl::enums::MessageEntity::TextUrl(tl::types::MessageEntityTextUrl {
+            offset: 0,
+            length: 4,
+            url: "https://evil.com/config".to_string(),
+        }),
+        tl::enums::MessageEntity::MentionName(tl::types::MessageEntityMentionName {
+            offset: 5,
+            length: 3,
+            user_id: 42,
+        }),
+        tl::enums::MessageEntity::Pre(tl::types::MessageEntityPre {
+            offset: 9,
+            length: 6,
+            language: "json".to_string(),
+        }),
+    ];
+
+    let parsed = parse_entities(&entities);
+    assert_eq!(parsed[0].entity_type, "text_url");
+    assert_eq!(parsed[0].url.as_deref(), Some("https://evil.com/config"));
+    assert_eq!(parsed[1].entity_type, "mention_name");
+    assert_eq!(parsed[1].user_id, Some(42));
+    assert_eq!(parsed[2].entity_type, "pre");
+    assert_eq!(parsed[2].language.as_deref(), Some("json"));
+}
@@ -0,0 +1,142 @@
+use crate::db::{ChatSyncState, Database, TelegramGroup};
+use crate::policy::CaptureMode;
+use crate::shutdown::Shutdown;
+use super::{flood_wait, list_dialog_chats, MessageProcessor};
+use grammers_client::{Client, InvocationError};
+use grammers_client::types::{Chat, ChatMap, Message};
+use grammers_tl_types as tl;
+use tracing::{info, warn};
+
+// Mensagens buscadas por requisição; cada página é salva antes da próxima
+const PAGE_SIZE: i32 = 100;
+
+pub struct GapRecovery {
+    database: Database,
+    processor: MessageProcessor,
+    shutdown: Shutdown,
+}
+
+impl GapRecovery {
+    pub fn new(database: Database, processor: MessageProcessor) -> Self {
+        Self {
+            database,
+            processor,
+            shutdown: Shutdown::new(),
+        }
+    }
+
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    pub async fn run(&self, client: &Client) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🩹 Verificando mensagens perdidas desde a última execução...");
+
+        let mut recovered = 0;
+        for chat in list_dialog_chats(client).await? {
+            if self.shutdown.is_requested() {
+                break;
+            }
+            if self.processor.capture_mode(&chat).await? == CaptureMode::Ignore {
+                continue;
+            }
+
+            match self.recover_chat(client, &chat).await {
+                Ok(count) => recovered += count,
+                Err(e) => warn!("❌ Erro ao recuperar mensagens de {} ({}): {}", chat.name(), chat.id(), e),
+            }
+        }
+
+        info!("✅ Recuperação concluída: {} mensagens recuperadas", recovered);
+        Ok(())
+    }
+
+    async fn recover_chat(&self, client: &Client, chat: &Chat) -> Result<usize, Box<dyn std::error::Error>> {
+        let pool = self.database.get_pool();
+
+        // Só chats já monitorados têm marca; os demais começam a partir das próximas atualizações
+        let Some(group) = TelegramGroup::find_by_telegram_id(pool, chat.id(), self.processor.chat_account(super::chat_type(chat))).await? else {
+            return Ok(0);
+        };
+        let Some(state) = ChatSyncState::find_by_group_id(pool, group.id).await? else {
+            return Ok(0);
+        };
+
+        // Página a página, da mais antiga para a mais nova: a marca avança a cada mensagem, então o
+        // restante é recuperado na próxima execução se o processo parar no meio
+        let mut after_id = state.last_message_id;
+        let mut recovered = 0;
+        while !self.shutdown.is_requested() {
+            let (page, fetched) = match messages_after(client, chat, after_id).await {
+                Ok(result) => result,
+                Err(e) => match flood_wait(&e) {
+                    Some(delay) => {
+                        warn!("⏳ FLOOD_WAIT ao recuperar {}, aguardando {}s", chat.name(), delay.as_secs());
+                        tokio::select! {
+                            _ = tokio::time::sleep(delay) => {},
+                            _ = self.shutdown.requested() => {},
+                        }
+                        continue;
+                    },
+                    None => return Err(e.into()),
+                },
+            };
+
+            let page_start = after_id;
+            for message in &page {
+                if self.shutdown.is_requested() {
+                    break;
+                }
+                if let Err(e) = self.processor.process_message(message).await {
+                    warn!("❌ Erro ao salvar mensagem {} da recuperação: {}", message.id(), e);
+                } else {
+                    recovered += 1;
+                }
+                after_id = message.id() as i64;
+            }
+
+            // A página cheia é medida antes de descartar mensagens vazias; se nada avançou, repetir
+            // a mesma requisição traria as mesmas mensagens
+            if fetched < PAGE_SIZE as usize || after_id == page_start {
+                break;
+            }
+        }
+
+        if recovered > 0 {
+            info!("📥 {}: {} mensagens após a mensagem {}", chat.name(), recovered, state.last_message_id);
+        }
+
+        Ok(recovered)
+    }
+}
+
+// Com add_offset negativo o histórico anda a partir de offset_id em direção às mensagens mais novas.
+// Devolve também quantas mensagens o Telegram retornou, antes de descartar as vazias
+async fn messages_after(client: &Client, chat: &Chat, after_id: i64) -> Result<(Vec<Message>, usize), InvocationError> {
+    let request = tl::functions::messages::GetHistory {
+        peer: chat.pack().to_input_peer(),
+        offset_id: after_id as i32 + 1,
+        offset_date: 0,
+        add_offset: -PAGE_SIZE,
+        limit: PAGE_SIZE,
+        max_id: 0,
+        min_id: after_id as i32,
+        hash: 0,
+    };
+
+    let (messages, users, chats) = match client.invoke(&request).await? {
+        tl::enums::messages::Messages::Messages(m) => (m.messages, m.users, m.chats),
+        tl::enums::messages::Messages::Slice(m) => (m.messages, m.users, m.chats),
+        tl::enums::messages::Messages::ChannelMessages(m) => (m.messages, m.users, m.chats),
+        tl::enums::messages::Messages::NotModified(_) => return Ok((Vec::new(), 0)),
+    };
+
+    let fetched = messages.len();
+    let chats = ChatMap::new(users, chats);
+    let mut page: Vec<Message> = messages.into_iter()
+        .filter_map(|message| Message::from_raw(client, message, &chats))
+        .collect();
+    page.sort_by_key(Message::id);
+    Ok((page, fetched))
+}
@@ -0,0 +1,526 @@
+use crate::config::CredentialSource;
+use async_trait::async_trait;
+use axum::extract::State;
+use axum::response::Html;
+use axum::routing::get;
+use axum::{Form, Router};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use grammers_client::types::PasswordToken;
+use grammers_client::client::auth::AuthorizationError;
+use grammers_client::session::Session;
+use grammers_client::{Client, Config, InvocationError, SignInError};
+use grammers_tl_types as tl;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+// Tempo para alguém entregar o código pelo arquivo ou pelo formulário antes de desistir
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(600);
+const FILE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum LoginError {
+    InvalidCode,
+    InvalidPassword,
+    SignUpRequired,
+    Unavailable(String),
+    Unsupported(String),
+    Telegram(InvocationError),
+}
+
+impl LoginError {
+    // Código e senha errados podem ser pedidos de novo; o resto exige intervenção
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, LoginError::InvalidCode | LoginError::InvalidPassword)
+    }
+}
+
+impl std::fmt::Display for LoginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoginError::InvalidCode => write!(f, "código de verificação inválido ou expirado"),
+            LoginError::InvalidPassword => write!(f, "senha 2FA incorreta"),
+            LoginError::SignUpRequired => write!(f, "número sem conta no Telegram: crie a conta pelo app oficial"),
+            LoginError::Unavailable(reason) => write!(f, "credenciais indisponíveis: {}", reason),
+            LoginError::Unsupported(reason) => write!(f, "login não suportado: {}", reason),
+            LoginError::Telegram(e) => write!(f, "erro do Telegram no login: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LoginError {}
+
+impl From<InvocationError> for LoginError {
+    fn from(e: InvocationError) -> Self {
+        LoginError::Telegram(e)
+    }
+}
+
+// Quem fornece o código e a senha; attempt começa em 1 e cresce a cada valor recusado
+#[async_trait]
+pub trait CredentialProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    // Falso quando o provedor não tem como responder (ex.: stdin sem terminal), antes de o Telegram enviar o código
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    async fn code(&self, phone: &str, attempt: u32) -> Result<String, LoginError>;
+
+    async fn password(&self, hint: Option<&str>, attempt: u32) -> Result<String, LoginError>;
+}
+
+pub fn provider_for(source: &CredentialSource) -> Arc<dyn CredentialProvider> {
+    match source {
+        CredentialSource::Stdin => Arc::new(StdinProvider),
+        CredentialSource::Env => Arc::new(EnvProvider),
+        CredentialSource::File(path) => Arc::new(FileProvider::new(path)),
+        CredentialSource::Http(bind) => Arc::new(HttpFormProvider::new(bind)),
+    }
+}
+
+pub struct StdinProvider;
+
+impl StdinProvider {
+    async fn ask(prompt: String, label: &'static str) -> Result<String, LoginError> {
+        tokio::task::spawn_blocking(move || {
+            println!("\n{}", prompt);
+            print!("{}: ", label);
+            io::stdout().flush()?;
+
+            let mut value = String::new();
+            io::stdin().read_line(&mut value)?;
+            Ok::<_, io::Error>(value.trim().to_string())
+        })
+        .await
+        .map_err(|e| LoginError::Unavailable(e.to_string()))?
+        .map_err(|e| LoginError::Unavailable(format!("falha ao ler do terminal: {}", e)))
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for StdinProvider {
+    fn name(&self) -> &str {
+        "stdin"
+    }
+
+    fn is_available(&self) -> bool {
+        io::stdin().is_terminal()
+    }
+
+    async fn code(&self, phone: &str, attempt: u32) -> Result<String, LoginError> {
+        let prompt = match attempt {
+            1 => format!("🔐 Digite o código de verificação enviado para {}:", phone),
+            _ => "🔐 Código recusado. Digite o código de verificação novamente:".to_string(),
+        };
+        Self::ask(prompt, "Código").await
+    }
+
+    async fn password(&self, hint: Option<&str>, attempt: u32) -> Result<String, LoginError> {
+        let prompt = match (attempt, hint) {
+            (1, Some(hint)) => format!("🔐 Digite sua senha 2FA (dica: {}):", hint),
+            (1, None) => "🔐 Digite sua senha 2FA:".to_string(),
+            _ => "🔐 Senha recusada. Digite sua senha 2FA novamente:".to_string(),
+        };
+        Self::ask(prompt, "Senha").await
+    }
+}
+
+// TELEGRAM_LOGIN_CODE e TELEGRAM_2FA_PASSWORD; lidas na hora, para não ficarem guardadas na Config
+pub struct EnvProvider;
+
+impl EnvProvider {
+    fn read(name: &str, attempt: u32) -> Result<String, LoginError> {
+        // O valor não muda entre tentativas: se foi recusado uma vez, será de novo
+        if attempt > 1 {
+            return Err(LoginError::Unavailable(format!("o valor em {} foi recusado", name)));
+        }
+        std::env::var(name)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| LoginError::Unavailable(format!("{} não definido", name)))
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for EnvProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    async fn code(&self, _phone: &str, attempt: u32) -> Result<String, LoginError> {
+        Self::read("TELEGRAM_LOGIN_CODE", attempt)
+    }
+
+    async fn password(&self, _hint: Option<&str>, attempt: u32) -> Result<String, LoginError> {
+        Self::read("TELEGRAM_2FA_PASSWORD", attempt)
+    }
+}
+
+// Arquivo comum (consumido depois de lido) ou named pipe (mkfifo), útil com kubectl exec ou docker exec
+pub struct FileProvider {
+    path: PathBuf,
+    timeout: Duration,
+}
+
+impl FileProvider {
+    pub fn new(path: &str) -> Self {
+        Self { path: PathBuf::from(path), timeout: PROMPT_TIMEOUT }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn ask(&self, label: &str) -> Result<String, LoginError> {
+        info!("✍️ Aguardando o {} em {} (ex.: echo VALOR > {})", label, self.path.display(), self.path.display());
+        let deadline = tokio::time::Instant::now() + self.timeout;
+
+        let expired = || LoginError::Unavailable(format!("nenhum {} escrito em {} a tempo", label, self.path.display()));
+        loop {
+            let path = self.path.clone();
+            let read = tokio::task::spawn_blocking(move || read_credential_file(&path));
+            let Ok(value) = tokio::time::timeout_at(deadline, read).await else {
+                release_pipe_reader(&self.path).await;
+                return Err(expired());
+            };
+            let value = value
+                .map_err(|e| LoginError::Unavailable(e.to_string()))?
+                .map_err(|e| LoginError::Unavailable(format!("falha ao ler {}: {}", self.path.display(), e)))?;
+            if let Some(value) = value {
+                return Ok(value);
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return Err(expired());
+            }
+            tokio::time::sleep(FILE_POLL_INTERVAL).await;
+        }
+    }
+}
+
+// A leitura de um pipe sem escritor continua presa numa thread e impediria o processo de encerrar;
+// abrir e fechar a ponta de escrita entrega um EOF a ela
+async fn release_pipe_reader(path: &std::path::Path) {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || std::fs::OpenOptions::new().write(true).open(path)).await.ok();
+}
+
+// Apaga o arquivo comum depois de ler: o mesmo valor não pode servir de código e de senha
+pub fn read_credential_file(path: &std::path::Path) -> io::Result<Option<String>> {
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    // Num pipe a leitura bloqueia até alguém escrever
+    let content = std::fs::read_to_string(path)?;
+    let value = content.lines().next().unwrap_or_default().trim().to_string();
+    if metadata.is_file() && !value.is_empty() {
+        std::fs::remove_file(path)?;
+    }
+
+    Ok(Some(value).filter(|value| !value.is_empty()))
+}
+
+#[async_trait]
+impl CredentialProvider for FileProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn code(&self, _phone: &str, attempt: u32) -> Result<String, LoginError> {
+        if attempt > 1 {
+            warn!("🔁 Código recusado, escreva outro");
+        }
+        self.ask("código de verificação").await
+    }
+
+    async fn password(&self, _hint: Option<&str>, attempt: u32) -> Result<String, LoginError> {
+        if attempt > 1 {
+            warn!("🔁 Senha recusada, escreva outra");
+        }
+        self.ask("senha 2FA").await
+    }
+}
+
+// Formulário servido só enquanto espera o valor; o bind padrão é local, para acesso via túnel ou port-forward
+pub struct HttpFormProvider {
+    bind: String,
+    timeout: Duration,
+}
+
+impl HttpFormProvider {
+    pub fn new(bind: &str) -> Self {
+        Self { bind: bind.to_string(), timeout: PROMPT_TIMEOUT }
+    }
+
+    async fn ask(&self, prompt: String, secret: bool) -> Result<String, LoginError> {
+        let listener = tokio::net::TcpListener::bind(&self.bind).await
+            .map_err(|e| LoginError::Unavailable(format!("falha ao abrir o formulário em {}: {}", self.bind, e)))?;
+        let address = listener.local_addr().map_err(|e| LoginError::Unavailable(e.to_string()))?;
+        info!("🌐 Informe as credenciais em http://{}/", address);
+
+        tokio::time::timeout(self.timeout, serve_form(listener, prompt, secret))
+            .await
+            .map_err(|_| LoginError::Unavailable("ninguém preencheu o formulário a tempo".to_string()))?
+    }
+}
+
+#[derive(Clone)]
+struct FormState {
+    page: Arc<String>,
+    sender: Arc<Mutex<Option<oneshot::Sender<String>>>>,
+}
+
+// Atende até receber um valor não vazio e então fecha o servidor
+pub async fn serve_form(listener: tokio::net::TcpListener, prompt: String, secret: bool) -> Result<String, LoginError> {
+    let (sender, receiver) = oneshot::channel();
+    let state = FormState {
+        page: Arc::new(form_page(&prompt, secret)),
+        sender: Arc::new(Mutex::new(Some(sender))),
+    };
+
+    let router = Router::new()
+        .route("/", get(show_form).post(submit_form))
+        .with_state(state);
+    let server = tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let value = receiver.await.map_err(|_| LoginError::Unavailable("formulário encerrado sem resposta".to_string()));
+    server.abort();
+    value
+}
+
+async fn show_form(State(state): State<FormState>) -> Html<String> {
+    Html(state.page.as_ref().clone())
+}
+
+async fn submit_form(State(state): State<FormState>, Form(fields): Form<HashMap<String, String>>) -> Html<String> {
+    let value = fields.get("value").map(|value| value.trim().to_string()).unwrap_or_default();
+    if value.is_empty() {
+        return Html(state.page.as_ref().clone());
+    }
+
+    match state.sender.lock().unwrap().take() {
+        Some(sender) => {
+            sender.send(value).ok();
+            Html("<p>Recebido. Pode fechar esta página.</p>".to_string())
+        },
+        None => Html("<p>Já recebido.</p>".to_string()),
+    }
+}
+
+fn form_page(prompt: &str, secret: bool) -> String {
+    let escaped = prompt.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+    format!(
+        "<!doctype html><meta charset=\"utf-8\"><title>F1000 login</title>\
+         <form method=\"post\"><label>{}<br><input name=\"value\" type=\"{}\" autofocus autocomplete=\"off\"></label> \
+         <button>Enviar</button></form>",
+        escaped,
+        if secret { "password" } else { "text" },
+    )
+}
+
+#[async_trait]
+impl CredentialProvider for HttpFormProvider {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn code(&self, phone: &str, attempt: u32) -> Result<String, LoginError> {
+        let prompt = match attempt {
+            1 => format!("Código de verificação enviado para {}", phone),
+            _ => "Código recusado. Informe o código de verificação novamente".to_string(),
+        };
+        self.ask(prompt, false).await
+    }
+
+    async fn password(&self, hint: Option<&str>, attempt: u32) -> Result<String, LoginError> {
+        let prompt = match (attempt, hint) {
+            (1, Some(hint)) => format!("Senha 2FA (dica: {})", hint),
+            (1, None) => "Senha 2FA".to_string(),
+            _ => "Senha recusada. Informe a senha 2FA novamente".to_string(),
+        };
+        self.ask(prompt, true).await
+    }
+}
+
+pub async fn sign_in_with_code(
+    client: &Client,
+    phone: &str,
+    credentials: &dyn CredentialProvider,
+    attempts: u32,
+) -> Result<(), LoginError> {
+    let login_token = client.request_login_code(phone).await
+        .map_err(|e| LoginError::Unavailable(format!("falha ao pedir o código: {}", e)))?;
+    info!("Código enviado para {}", phone);
+
+    let mut last_error = LoginError::InvalidCode;
+    for attempt in 1..=attempts {
+        let code = credentials.code(phone, attempt).await?;
+        match client.sign_in(&login_token, &code).await {
+            Ok(_) => return Ok(()),
+            Err(SignInError::PasswordRequired(password_token)) => {
+                return check_password(client, password_token, credentials, attempts).await;
+            },
+            Err(SignInError::InvalidCode) => {
+                warn!("❌ Código recusado ({}/{})", attempt, attempts);
+                last_error = LoginError::InvalidCode;
+            },
+            Err(SignInError::SignUpRequired { .. }) => return Err(LoginError::SignUpRequired),
+            Err(SignInError::InvalidPassword) => return Err(LoginError::InvalidPassword),
+            Err(SignInError::Other(e)) => return Err(LoginError::Telegram(e)),
+        }
+    }
+
+    Err(last_error)
+}
+
+pub async fn check_password(
+    client: &Client,
+    mut password_token: PasswordToken,
+    credentials: &dyn CredentialProvider,
+    attempts: u32,
+) -> Result<(), LoginError> {
+    for attempt in 1..=attempts {
+        let password = credentials.password(password_token.hint(), attempt).await?;
+        match client.check_password(password_token, password).await {
+            Ok(_) => return Ok(()),
+            Err(SignInError::InvalidPassword) => {
+                warn!("❌ Senha 2FA recusada ({}/{})", attempt, attempts);
+                // O token carrega parâmetros SRP de uso único: cada tentativa precisa de um novo
+                password_token = password_information(client).await?;
+            },
+            Err(SignInError::Other(e)) => return Err(LoginError::Telegram(e)),
+            Err(e) => return Err(LoginError::Unavailable(e.to_string())),
+        }
+    }
+
+    Err(LoginError::InvalidPassword)
+}
+
+async fn password_information(client: &Client) -> Result<PasswordToken, LoginError> {
+    let tl::enums::account::Password::Password(password) = client.invoke(&tl::functions::account::GetPassword {}).await?;
+    Ok(PasswordToken::new(password))
+}
+
+// O app já logado escaneia o QR (Configurações > Dispositivos > Conectar dispositivo).
+// config monta a configuração do cliente caso seja preciso reconectar em outro DC
+pub async fn sign_in_with_qr(
+    client: &mut Client,
+    api_id: i32,
+    api_hash: &str,
+    config: impl Fn(Session) -> Config,
+    credentials: &dyn CredentialProvider,
+    attempts: u32,
+) -> Result<(), LoginError> {
+    let mut migrated_token = None;
+    loop {
+        let exported = match migrated_token.take() {
+            Some(token) => client.invoke(&tl::functions::auth::ImportLoginToken { token }).await,
+            None => client.invoke(&tl::functions::auth::ExportLoginToken {
+                api_id,
+                api_hash: api_hash.to_string(),
+                except_ids: Vec::new(),
+            }).await,
+        };
+
+        match exported {
+            Ok(tl::enums::auth::LoginToken::Token(token)) => {
+                let url = login_url(&token.token);
+                match render_qr(&url) {
+                    Some(qr) => eprintln!("\n📱 Escaneie com o app do Telegram (Configurações > Dispositivos > Conectar dispositivo):\n{}", qr),
+                    None => eprintln!("\n📱 Abra no app do Telegram: {}", url),
+                }
+
+                // O token expira em ~30s; ao ser aceito o Telegram avisa com UpdateLoginToken e a próxima exportação traz o login
+                let expires_in = (token.expires as i64 - chrono::Utc::now().timestamp()).max(1) as u64;
+                tokio::time::timeout(Duration::from_secs(expires_in), wait_for_login_token(client)).await.ok();
+            },
+            Ok(tl::enums::auth::LoginToken::Success(success)) => return complete_qr_login(client, success.authorization),
+            // A conta é de outro DC: o token aceito só pode ser importado lá
+            Ok(tl::enums::auth::LoginToken::MigrateTo(migrate)) => {
+                info!("🔀 Conta no DC {}, reconectando para concluir o login", migrate.dc_id);
+                *client = connect_to_dc(client, migrate.dc_id, &config).await?;
+                migrated_token = Some(migrate.token);
+            },
+            Err(e) if e.is("SESSION_PASSWORD_NEEDED") => {
+                let password_token = password_information(client).await?;
+                return check_password(client, password_token, credentials, attempts).await;
+            },
+            Err(e) => return Err(LoginError::Telegram(e)),
+        }
+    }
+}
+
+pub fn login_url(token: &[u8]) -> String {
+    format!("tg://login?token={}", URL_SAFE_NO_PAD.encode(token))
+}
+
+pub fn render_qr(url: &str) -> Option<String> {
+    use qrcode::render::unicode::Dense1x2;
+
+    let code = qrcode::QrCode::new(url.as_bytes()).ok()?;
+    // Cores invertidas: a maioria dos terminais tem fundo escuro
+    Some(code.render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+// O grammers conecta no DC do usuário da sessão; até o login terminar o usuário é só um marcador,
+// substituído em complete_qr_login
+async fn connect_to_dc(client: &Client, dc_id: i32, config: &impl Fn(Session) -> Config) -> Result<Client, LoginError> {
+    let session = Session::load(&client.session().save())
+        .map_err(|e| LoginError::Unsupported(format!("falha ao copiar a sessão: {}", e)))?;
+    session.set_user(0, dc_id, false);
+    match Client::connect(config(session)).await {
+        Ok(client) => Ok(client),
+        Err(AuthorizationError::Invoke(e)) => Err(LoginError::Telegram(e)),
+        Err(e) => Err(LoginError::Unsupported(format!("falha ao conectar ao DC {}: {}", dc_id, e))),
+    }
+}
+
+// Sem sessão autorizada o fluxo de updates pode falhar; nesse caso só resta esperar o token expirar
+async fn wait_for_login_token(client: &Client) {
+    loop {
+        match client.next_raw_update().await {
+            Ok((tl::enums::Update::LoginToken, _)) => return,
+            Ok(_) => {},
+            Err(_) => std::future::pending::<()>().await,
+        }
+    }
+}
+
+// Equivalente ao que o grammers faz após sign_in: registra o usuário e o DC na sessão
+fn complete_qr_login(client: &Client, authorization: tl::enums::auth::Authorization) -> Result<(), LoginError> {
+    let tl::enums::auth::Authorization::Authorization(authorization) = authorization else {
+        return Err(LoginError::SignUpRequired);
+    };
+    let tl::enums::User::User(user) = authorization.user else {
+        return Err(LoginError::Unsupported("o Telegram devolveu um usuário vazio".to_string()));
+    };
+
+    let session = client.session();
+    // Depois de uma migração o DC é o do marcador; senão a sessão nova só tem chave do DC em que o cliente conectou
+    let dc = session.get_user().map(|user| user.dc)
+        .or_else(|| session.get_dcs().into_iter().find(|dc| dc.auth.is_some()).map(|dc| dc.id))
+        .ok_or_else(|| LoginError::Unsupported("DC da sessão desconhecido".to_string()))?;
+    session.set_user(user.id, dc, user.bot);
+
+    info!("✅ Login por QR concluído (usuário {})", user.id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
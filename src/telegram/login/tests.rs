@@ -0,0 +1,82 @@
+use super::*;
+
+#[test]
+fn only_wrong_codes_and_passwords_are_retryable() {
+    assert!(LoginError::InvalidCode.is_retryable());
+    assert!(LoginError::InvalidPassword.is_retryable());
+    assert!(!LoginError::SignUpRequired.is_retryable());
+    assert!(!LoginError::Unavailable("TELEGRAM_LOGIN_CODE não definido".to_string()).is_retryable());
+}
+
+#[tokio::test]
+async fn env_provider_does_not_repeat_a_rejected_value() {
+    std::env::set_var("TELEGRAM_LOGIN_CODE", " 12345\n");
+    let provider = EnvProvider;
+
+    assert_eq!(provider.code("+5511999999999", 1).await.unwrap(), "12345");
+    assert!(matches!(provider.code("+5511999999999", 2).await, Err(LoginError::Unavailable(_))));
+    std::env::remove_var("TELEGRAM_LOGIN_CODE");
+}
+
+#[tokio::test]
+async fn file_provider_waits_for_the_value_and_consumes_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("login-code");
+    let provider = FileProvider::new(path.to_str().unwrap()).with_timeout(Duration::from_secs(5));
+
+    let writer = {
+        let path = path.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            std::fs::write(&path, "54321\nlixo\n").unwrap();
+        })
+    };
+
+    assert_eq!(provider.code("+5511999999999", 1).await.unwrap(), "54321");
+    writer.await.unwrap();
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn file_provider_gives_up_after_the_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let provider = FileProvider::new(dir.path().join("ausente").to_str().unwrap()).with_timeout(Duration::from_millis(10));
+
+    assert!(matches!(provider.password(None, 1).await, Err(LoginError::Unavailable(_))));
+}
+
+#[tokio::test]
+async fn file_provider_does_not_wait_forever_on_a_pipe_nobody_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("login-code");
+    assert!(std::process::Command::new("mkfifo").arg(&path).status().unwrap().success());
+    let provider = FileProvider::new(path.to_str().unwrap()).with_timeout(Duration::from_millis(100));
+
+    let result = tokio::time::timeout(Duration::from_secs(5), provider.code("+5511999999999", 1)).await;
+    assert!(matches!(result, Ok(Err(LoginError::Unavailable(_)))));
+}
+
+#[tokio::test]
+async fn http_form_returns_the_submitted_value() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let form = tokio::spawn(serve_form(listener, "Senha 2FA (dica: <gato>)".to_string(), true));
+
+    let client = reqwest::Client::new();
+    let page = client.get(&url).send().await.unwrap().text().await.unwrap();
+    assert!(page.contains("dica: &lt;gato&gt;"));
+    assert!(page.contains("type=\"password\""));
+
+    // Envio vazio só mostra o formulário de novo
+    client.post(&url).form(&[("value", " ")]).send().await.unwrap();
+    client.post(&url).form(&[("value", "hunter2")]).send().await.unwrap();
+
+    assert_eq!(form.await.unwrap().unwrap(), "hunter2");
+}
+
+#[test]
+fn qr_login_url_uses_unpadded_url_safe_base64() {
+    let url = login_url(&[0xfb, 0xff, 0x01]);
+    assert_eq!(url, "tg://login?token=-_8B");
+    assert!(render_qr(&url).is_some_and(|qr| qr.lines().count() > 10));
+}
@@ -1,255 +1,676 @@
-use crate::db::{Database, NewTelegramUser, NewTelegramGroup, NewTelegramMessage, TelegramUser, TelegramGroup, TelegramMessage};
-use grammers_client::types::{Message, User, Chat};
+use crate::db::{Database, ChatSyncState, NewTelegramUser, NewTelegramGroup, NewTelegramMessage, NewTelegramMessageVersion, NewMessageEntity, NewAlert, Alert, Ioc, MessageEntity, TelegramUser, TelegramGroup, TelegramMessage, TelegramMessageVersion};
+use crate::ioc::extract_iocs;
+use crate::media::MediaDownloader;
+use crate::notify::NotificationDispatcher;
+use crate::policy::{CaptureMode, PolicyCache, PolicySet};
+use crate::shutdown::TaskTracker;
+use crate::watchlist::WatchlistCache;
+use super::claims::ChatClaims;
+use super::entities::{entity_text, parse_entities, MessageEntityInfo};
+use grammers_client::types::{Message, MessageDeletion, Media, Chat};
+use grammers_tl_types as tl;
 use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::sync::Arc;
 use tracing::{info, warn};
 use uuid::Uuid;
 
+#[derive(Clone)]
 pub struct MessageProcessor {
     database: Database,
+    media_downloader: Option<MediaDownloader>,
+    notification_dispatcher: Option<NotificationDispatcher>,
+    watchlist: WatchlistCache,
+    policies: PolicyCache,
+    ignored_chats: Arc<HashSet<i64>>,
+    account_id: Option<String>,
+    claims: ChatClaims,
+    tasks: TaskTracker,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ForwardPeer {
+    User(i64),
+    Group(i64),
+    Channel(i64),
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ForwardOrigin {
+    peer: Option<ForwardPeer>,
+    date: Option<DateTime<Utc>>,
+    message_id: Option<i64>,
+    sender_name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct MediaInfo {
+    message_type: Option<&'static str>,
+    file_id: Option<String>,
+    file_unique_id: Option<String>,
+    file_size: Option<i64>,
+    mime_type: Option<String>,
+    file_name: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    contact_phone_number: Option<String>,
+    contact_first_name: Option<String>,
+    contact_last_name: Option<String>,
 }
 
 impl MessageProcessor {
     pub fn new(database: Database) -> Self {
-        Self { database }
+        Self {
+            watchlist: WatchlistCache::new(database.clone()),
+            policies: PolicyCache::new(database.clone(), PolicySet::default()),
+            database,
+            media_downloader: None,
+            notification_dispatcher: None,
+            ignored_chats: Arc::new(HashSet::new()),
+            account_id: None,
+            claims: ChatClaims::default(),
+            tasks: TaskTracker::new(),
+        }
+    }
+
+    pub fn with_media_downloader(mut self, media_downloader: MediaDownloader) -> Self {
+        self.media_downloader = Some(media_downloader);
+        self
+    }
+
+    pub fn with_notification_dispatcher(mut self, notification_dispatcher: NotificationDispatcher) -> Self {
+        self.notification_dispatcher = Some(notification_dispatcher);
+        self
     }
-    
-    pub async fn process_message(&self, message: Message) -> Result<(), Box<dyn std::error::Error>> {
+
+    // Chats que nunca são coletados, como o chat de alertas (evita que um alerta gere outro alerta)
+    pub fn with_ignored_chat(mut self, chat_id: i64) -> Self {
+        Arc::make_mut(&mut self.ignored_chats).insert(chat_id);
+        self
+    }
+
+    pub fn with_policies(mut self, policies: PolicyCache) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    // Conta coletora gravada em cada mensagem; os chats compartilhados com outras contas passam pelas claims
+    pub fn with_account(mut self, account_id: &str, claims: ChatClaims) -> Self {
+        self.account_id = Some(account_id.to_string());
+        self.claims = claims;
+        self
+    }
+
+    // Downloads e notificações disparados pelo processamento, aguardados no encerramento
+    pub fn tasks(&self) -> &TaskTracker {
+        &self.tasks
+    }
+
+    pub fn ignores_chat(&self, chat_id: i64) -> bool {
+        self.ignored_chats.contains(&chat_id)
+    }
+
+    // Avaliada antes de qualquer escrita: chats ignorados pela política nem chegam a ser disputados entre as contas
+    pub async fn capture_mode(&self, chat: &Chat) -> Result<CaptureMode, sqlx::Error> {
+        if self.ignores_chat(chat.id()) {
+            return Ok(CaptureMode::Ignore);
+        }
+
+        let chat_type = super::chat_type(chat);
+        let mode = self.policies.current().await?.mode_for(chat.id(), chat.username(), chat_type);
+        if mode == CaptureMode::Ignore {
+            return Ok(mode);
+        }
+
+        let collects = match &self.account_id {
+            Some(account_id) if super::is_shared_chat(chat_type) => self.claims.claim(account_id, chat.id(), chat.username()),
+            Some(account_id) => self.claims.allows(account_id, chat.id(), chat.username()),
+            None => true,
+        };
+        Ok(if collects { mode } else { CaptureMode::Ignore })
+    }
+
+    // Conta gravada na linha do chat: só chats privados e grupos básicos têm uma linha por conta
+    pub fn chat_account(&self, chat_type: &str) -> Option<&str> {
+        self.account_id.as_deref().filter(|_| !super::is_shared_chat(chat_type))
+    }
+
+    pub async fn process_message(&self, message: &Message) -> Result<Option<TelegramMessage>, Box<dyn std::error::Error>> {
+        let mode = self.capture_mode(&message.chat()).await?;
+        if mode == CaptureMode::Ignore {
+            return Ok(None);
+        }
+
+        let user_id = match message.sender() {
+            Some(Chat::User(user)) => match self.process_user(user).await {
+                Ok(telegram_user) => Some(telegram_user.id),
+                Err(e) => {
+                    warn!("❌ Erro ao processar usuário: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        let telegram_group = self.process_chat(message.chat()).await?;
+        let saved = self.process_message_content(message, user_id, Some(telegram_group.id), mode).await?;
+
+        if let Some(saved) = saved.as_ref().filter(|_| mode.stores_text()) {
+            let entities = parse_entities(message.fmt_entities().map(Vec::as_slice).unwrap_or_default());
+            if let Err(e) = self.process_entities(saved.id, message.text(), &entities).await {
+                warn!("❌ Erro ao salvar entidades da mensagem {}: {}", saved.telegram_message_id, e);
+            }
+            if let Err(e) = self.process_iocs(saved.id, message.text(), &entities, saved.date).await {
+                warn!("❌ Erro ao extrair IOCs da mensagem {}: {}", saved.telegram_message_id, e);
+            }
+            if let Err(e) = self.process_alerts(saved.id, saved.group_id, message.text()).await {
+                warn!("❌ Erro ao avaliar watchlists da mensagem {}: {}", saved.telegram_message_id, e);
+            }
+        }
+
+        if let (Some(media_downloader), Some(saved)) = (&self.media_downloader, &saved) {
+            if mode.downloads_media() && media_downloader.should_download(&telegram_group, saved) {
+                self.tasks.track(media_downloader.spawn(message.clone(), saved.clone()));
+            }
+        }
+
+        Ok(saved)
+    }
+
+    pub async fn process_edit(&self, message: &Message) -> Result<Option<TelegramMessageVersion>, Box<dyn std::error::Error>> {
+        let mode = self.capture_mode(&message.chat()).await?;
+        if mode == CaptureMode::Ignore {
+            return Ok(None);
+        }
+
         let pool = self.database.get_pool();
-        
-        let user_id = if let Some(sender) = message.sender() {
-            let telegram_user = self.process_user(sender).await?;
-            Some(telegram_user.id)
-        } else {
-            None
+        let group = self.process_chat(message.chat()).await?;
+
+        let Some(message_id) = TelegramMessage::find_id_by_telegram_id(pool, group.id, message.id() as i64).await? else {
+            // Edição de uma mensagem que nunca foi capturada: o estado atual vira a mensagem original
+            self.process_message(message).await?;
+            return Ok(None);
         };
-        
-        let group_id = {
-            let telegram_group = self.process_chat(message.chat()).await?;
-            Some(telegram_group.id)
+        // Sem o texto original não há o que versionar
+        if !mode.stores_text() {
+            return Ok(None);
+        }
+
+        let message_text = Some(message.text().to_string()).filter(|text| !text.is_empty());
+        let parsed_entities = parse_entities(message.fmt_entities().map(Vec::as_slice).unwrap_or_default());
+        let entities = serde_json::to_value(&parsed_entities)?;
+
+        let Some(timeline) = TelegramMessage::edit_timeline(pool, message_id).await? else {
+            return Ok(None);
+        };
+        // Reações e contadores também chegam como edição; só registra quando o conteúdo muda
+        let unchanged = match timeline.versions.last() {
+            Some(last) => last.message_text == message_text && last.entities == entities,
+            None => timeline.original.message_text == message_text,
+        };
+        if unchanged {
+            return Ok(None);
+        }
+
+        let edit_date = message.edit_date().unwrap_or_else(Utc::now);
+        let new_version = NewTelegramMessageVersion {
+            message_id,
+            message_text,
+            edit_date,
+            entities,
         };
-        
-        self.process_message_content(message, user_id, group_id).await?;
-        
+
+        let version = match TelegramMessageVersion::create(pool, new_version).await {
+            Ok(version) => version,
+            Err(sqlx::Error::RowNotFound) => return Ok(None),
+            Err(e) => return Err(format!("Erro ao salvar edição da mensagem {}: {}", message.id(), e).into()),
+        };
+        TelegramMessage::update_edit_date(pool, message_id, edit_date).await?;
+
+        // Entidades e indicadores inseridos numa edição também ficam associados à mensagem
+        if let Err(e) = self.process_entities(message_id, message.text(), &parsed_entities).await {
+            warn!("❌ Erro ao salvar entidades da edição da mensagem {}: {}", message.id(), e);
+        }
+        if let Err(e) = self.process_iocs(message_id, message.text(), &parsed_entities, edit_date).await {
+            warn!("❌ Erro ao extrair IOCs da edição da mensagem {}: {}", message.id(), e);
+        }
+        if let Err(e) = self.process_alerts(message_id, Some(group.id), message.text()).await {
+            warn!("❌ Erro ao avaliar watchlists da edição da mensagem {}: {}", message.id(), e);
+        }
+
+        info!("✏️ Edição registrada: mensagem {} (versão {})", message.id(), timeline.versions.len() + 1);
+        Ok(Some(version))
+    }
+
+    pub async fn process_deletion(&self, deletion: &MessageDeletion) -> Result<u64, Box<dyn std::error::Error>> {
+        let collected_elsewhere = |channel_id: i64| self.claims.owner(channel_id)
+            .is_some_and(|owner| self.account_id.as_deref() != Some(owner.as_str()));
+        if deletion.channel_id().is_some_and(|channel_id| self.ignores_chat(channel_id) || collected_elsewhere(channel_id)) {
+            return Ok(0);
+        }
+
+        let pool = self.database.get_pool();
+        let message_ids: Vec<i64> = deletion.messages().iter().map(|id| *id as i64).collect();
+
+        let deletion_source = if deletion.channel_id().is_some() { "channel_update" } else { "chat_update" };
+        let deleted = TelegramMessage::mark_deleted(pool, deletion.channel_id(), &message_ids, deletion_source, self.account_id.as_deref()).await?;
+
+        if deleted > 0 {
+            info!("🗑️ {} mensagens marcadas como apagadas ({})", deleted, deletion_source);
+        }
+
+        Ok(deleted)
+    }
+
+    async fn process_entities(&self, message_id: Uuid, text: &str, entities: &[MessageEntityInfo]) -> Result<(), sqlx::Error> {
+        let pool = self.database.get_pool();
+
+        for entity in entities {
+            let new_entity = NewMessageEntity {
+                message_id,
+                entity_type: entity.entity_type.clone(),
+                offset: entity.offset,
+                length: entity.length,
+                url: entity.url.clone(),
+                mentioned_user_id: entity.user_id,
+                language: entity.language.clone(),
+                entity_text: entity_text(text, entity.offset, entity.length),
+            };
+
+            match MessageEntity::create(pool, new_entity).await {
+                Ok(_) | Err(sqlx::Error::RowNotFound) => {},
+                Err(e) => return Err(e),
+            }
+        }
+
         Ok(())
     }
-    
-    async fn process_user(&self, user: User) -> Result<TelegramUser, Box<dyn std::error::Error>> {
+
+    async fn process_iocs(
+        &self,
+        message_id: Uuid,
+        text: &str,
+        entities: &[MessageEntityInfo],
+        seen_at: DateTime<Utc>,
+    ) -> Result<usize, sqlx::Error> {
         let pool = self.database.get_pool();
-        
+
+        // Links escondidos atrás de texto (text_url) não aparecem em message.text()
+        let mut input = text.to_string();
+        for url in entities.iter().filter_map(|entity| entity.url.as_deref()) {
+            input.push('\n');
+            input.push_str(url);
+        }
+        let iocs = extract_iocs(&input);
+
+        for ioc in &iocs {
+            Ioc::record_sighting(pool, message_id, ioc.ioc_type.as_str(), &ioc.value, seen_at).await?;
+        }
+
+        if !iocs.is_empty() {
+            info!("🔎 {} IOCs extraídos da mensagem", iocs.len());
+        }
+
+        Ok(iocs.len())
+    }
+
+    async fn process_alerts(&self, message_id: Uuid, group_id: Option<Uuid>, text: &str) -> Result<Vec<Alert>, sqlx::Error> {
+        let watchlist = self.watchlist.current().await?;
+        if watchlist.is_empty() || text.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut alerts = Vec::new();
+        for found in watchlist.find_matches(text) {
+            let new_alert = NewAlert {
+                watchlist_id: found.rule_id,
+                message_id,
+                group_id,
+                match_start: found.start,
+                match_end: found.end,
+                matched_text: found.matched_text,
+            };
+
+            // A mesma ocorrência reaparece em edições que não mexem no trecho; não gera alerta de novo
+            match Alert::create(self.database.get_pool(), new_alert).await {
+                Ok(alert) => {
+                    info!("🚨 Alerta de watchlist: '{}' na mensagem {}", alert.matched_text, message_id);
+                    alerts.push(alert);
+                },
+                Err(sqlx::Error::RowNotFound) => {},
+                Err(e) => return Err(e),
+            }
+        }
+
+        if let Some(notification_dispatcher) = &self.notification_dispatcher {
+            if !alerts.is_empty() {
+                self.tasks.track(notification_dispatcher.spawn(alerts.clone()));
+            }
+        }
+
+        Ok(alerts)
+    }
+
+    async fn process_user(&self, user: grammers_client::types::User) -> Result<TelegramUser, Box<dyn std::error::Error>> {
+        let pool = self.database.get_pool();
+
+        // Usuários conhecidos só por encaminhamentos não têm nome ainda e são completados aqui
         if let Some(existing_user) = TelegramUser::find_by_telegram_id(pool, user.id()).await? {
-            return Ok(existing_user);
+            if existing_user.first_name.is_some() {
+                return Ok(existing_user);
+            }
         }
-        
+
         let new_user = NewTelegramUser {
             telegram_user_id: user.id(),
             username: user.username().map(|s| s.to_string()),
-            first_name: user.first_name().map(|s| s.to_string()),
+            first_name: Some(user.first_name().to_string()),
             last_name: user.last_name().map(|s| s.to_string()),
-            phone_number: None, // Não disponível via API
+            phone_number: user.phone().map(|s| s.to_string()),
             is_bot: user.is_bot(),
-            is_verified: user.is_verified(),
-            is_premium: user.is_premium(),
-            language_code: user.language_code().map(|s| s.to_string()),
+            is_verified: user.verified(),
+            is_premium: user.raw.premium,
+            language_code: user.lang_code().map(|s| s.to_string()),
         };
-        
+
         let telegram_user = TelegramUser::create(pool, new_user).await?;
-        info!("👤 Usuário processado: {} ({})", 
-            telegram_user.first_name.as_deref().unwrap_or("Sem nome"), 
+        info!("👤 Usuário processado: {} ({})",
+            telegram_user.first_name.as_deref().unwrap_or("Sem nome"),
             telegram_user.telegram_user_id);
-        
+
         Ok(telegram_user)
     }
-    
-    async fn process_chat(&self, chat: Chat) -> Result<TelegramGroup, Box<dyn std::error::Error>> {
+
+    pub async fn process_chat(&self, chat: Chat) -> Result<TelegramGroup, Box<dyn std::error::Error>> {
         let pool = self.database.get_pool();
-        
 
-        if let Some(existing_group) = TelegramGroup::find_by_telegram_id(pool, chat.id()).await? {
-            return Ok(existing_group);
+        let chat_type = super::chat_type(&chat).to_string();
+        let collector_account_id = self.chat_account(&chat_type).map(str::to_string);
+
+        if let Some(existing_group) = TelegramGroup::find_by_telegram_id(pool, chat.id(), collector_account_id.as_deref()).await? {
+            if existing_group.title.is_some() {
+                return Ok(existing_group);
+            }
         }
-        
-        let chat_type = match chat {
-            Chat::User(_) => "private",
-            Chat::Group(_) => "group",
-            Chat::Supergroup(_) => "supergroup",
-            Chat::Channel(_) => "channel",
-        }.to_string();
-        
-        let (title, username, description, member_count) = match chat {
-            Chat::User(user) => (
-                Some(format!("{} {}", 
-                    user.first_name(), 
-                    user.last_name().unwrap_or("")
-                ).trim().to_string()),
+
+        let (title, username, is_verified, is_restricted, is_scam, is_fake) = match chat {
+            Chat::User(ref user) => (
+                Some(user.full_name()),
                 user.username().map(|s| s.to_string()),
-                None,
-                None
+                user.verified(),
+                user.restricted(),
+                user.scam(),
+                user.raw.fake,
             ),
-            Chat::Group(group) => (
+            Chat::Group(ref group) => (
                 Some(group.title().to_string()),
-                None,
-                None,
-                None
+                group.username().map(|s| s.to_string()),
+                false,
+                false,
+                false,
+                false,
             ),
-            Chat::Supergroup(supergroup) => (
-                Some(supergroup.title().to_string()),
-                supergroup.username().map(|s| s.to_string()),
-                supergroup.description().map(|s| s.to_string()),
-                None // member_count não disponível diretamente
-            ),
-            Chat::Channel(channel) => (
+            Chat::Channel(ref channel) => (
                 Some(channel.title().to_string()),
                 channel.username().map(|s| s.to_string()),
-                channel.description().map(|s| s.to_string()),
-                None
+                channel.raw.verified,
+                channel.raw.restricted,
+                channel.raw.scam,
+                channel.raw.fake,
             ),
         };
-        
-        // Criar novo grupo
+
         let new_group = NewTelegramGroup {
             telegram_chat_id: chat.id(),
             chat_type,
             title,
             username,
-            description,
-            invite_link: None, 
-            member_count,
-            is_verified: false, 
-            is_restricted: false, 
-            is_scam: false, 
-            is_fake: false, 
+            description: None,
+            invite_link: None,
+            member_count: None,
+            is_verified,
+            is_restricted,
+            is_scam,
+            is_fake,
+            collector_account_id,
         };
-        
+
         let telegram_group = TelegramGroup::create(pool, new_group).await?;
-        info!("👥 Grupo processado: {} ({})", 
-            telegram_group.title.as_deref().unwrap_or("Sem título"), 
+        info!("👥 Grupo processado: {} ({})",
+            telegram_group.title.as_deref().unwrap_or("Sem título"),
             telegram_group.telegram_chat_id);
-        
+
         Ok(telegram_group)
     }
-    
+
+    // Só o ID da origem vem no cabeçalho; cria um registro mínimo que é completado quando o chat/usuário aparecer
+    async fn process_forward_peer(&self, peer: ForwardPeer) -> Result<(Option<Uuid>, Option<Uuid>), Box<dyn std::error::Error>> {
+        let pool = self.database.get_pool();
+
+        let (telegram_chat_id, chat_type) = match peer {
+            ForwardPeer::User(telegram_user_id) => {
+                let user = match TelegramUser::find_by_telegram_id(pool, telegram_user_id).await? {
+                    Some(user) => user,
+                    None => TelegramUser::create(pool, NewTelegramUser {
+                        telegram_user_id,
+                        username: None,
+                        first_name: None,
+                        last_name: None,
+                        phone_number: None,
+                        is_bot: false,
+                        is_verified: false,
+                        is_premium: false,
+                        language_code: None,
+                    }).await?,
+                };
+                return Ok((Some(user.id), None));
+            },
+            ForwardPeer::Group(telegram_chat_id) => (telegram_chat_id, "group"),
+            ForwardPeer::Channel(telegram_chat_id) => (telegram_chat_id, "channel"),
+        };
+
+        let collector_account_id = self.chat_account(chat_type);
+        let group = match TelegramGroup::find_by_telegram_id(pool, telegram_chat_id, collector_account_id).await? {
+            Some(group) => group,
+            None => TelegramGroup::create(pool, NewTelegramGroup {
+                telegram_chat_id,
+                chat_type: chat_type.to_string(),
+                title: None,
+                username: None,
+                description: None,
+                invite_link: None,
+                member_count: None,
+                is_verified: false,
+                is_restricted: false,
+                is_scam: false,
+                is_fake: false,
+                collector_account_id: collector_account_id.map(str::to_string),
+            }).await?,
+        };
+        Ok((None, Some(group.id)))
+    }
+
     async fn process_message_content(
-        &self, 
-        message: Message, 
-        user_id: Option<Uuid>, 
-        group_id: Option<Uuid>
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        &self,
+        message: &Message,
+        user_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+        mode: CaptureMode,
+    ) -> Result<Option<TelegramMessage>, Box<dyn std::error::Error>> {
         let pool = self.database.get_pool();
-        
-        // Determinar tipo da mensagem
-        let message_type = if message.text().is_some() {
+
+        let message_text = Some(message.text().to_string()).filter(|text| !text.is_empty());
+        let media = message.media().map(|media| extract_media(&media)).unwrap_or_default();
+
+        let forward = message.forward_header().map(|header| parse_forward_header(&header)).unwrap_or_default();
+        let (forward_from_user_id, forward_from_group_id) = match forward.peer {
+            Some(peer) => self.process_forward_peer(peer).await?,
+            None => (None, None),
+        };
+
+        let message_type = if message.action().is_some() {
+            "service"
+        } else if let Some(media_type) = media.message_type {
+            media_type
+        } else if message_text.is_some() {
             "text"
-        } else if message.photo().is_some() {
-            "photo"
-        } else if message.video().is_some() {
-            "video"
-        } else if message.audio().is_some() {
-            "audio"
-        } else if message.document().is_some() {
-            "document"
-        } else if message.sticker().is_some() {
-            "sticker"
-        } else if message.location().is_some() {
-            "location"
-        } else if message.contact().is_some() {
-            "contact"
         } else {
             "unknown"
         }.to_string();
-        
-       
-        let message_text = message.text().map(|s| s.to_string());
-        
-        
-        let (media_file_id, media_file_unique_id, media_file_size, media_mime_type, media_file_name) = 
-            if let Some(photo) = message.photo() {
-                let file = photo.media();
-                (
-                    Some(file.id().to_string()),
-                    Some(file.file_unique_id().to_string()),
-                    None, 
-                    Some("image/jpeg".to_string()),
-                    None
-                )
-            } else if let Some(video) = message.video() {
-                let file = video.media();
-                (
-                    Some(file.id().to_string()),
-                    Some(file.file_unique_id().to_string()),
-                    None,
-                    Some("video/mp4".to_string()),
-                    None
-                )
-            } else if let Some(document) = message.document() {
-                let file = document.media();
-                (
-                    Some(file.id().to_string()),
-                    Some(file.file_unique_id().to_string()),
-                    None,
-                    document.mime_type().map(|s| s.to_string()),
-                    document.file_name().map(|s| s.to_string())
-                )
-            } else {
-                (None, None, None, None, None)
-            };
-        
-       
-        let (location_latitude, location_longitude) = if let Some(location) = message.location() {
-            (Some(location.latitude()), Some(location.longitude()))
-        } else {
-            (None, None)
-        };
-        
-       
-        let (contact_phone_number, contact_first_name, contact_last_name) = if let Some(contact) = message.contact() {
-            (
-                Some(contact.phone_number().to_string()),
-                Some(contact.first_name().to_string()),
-                contact.last_name().map(|s| s.to_string())
-            )
-        } else {
-            (None, None, None)
-        };
-        
-        let new_message = NewTelegramMessage {
-            telegram_message_id: message.id(),
+
+        let mut new_message = NewTelegramMessage {
+            telegram_message_id: message.id() as i64,
             user_id,
             group_id,
             message_text,
             message_type,
-            date: DateTime::from_timestamp(message.date() as i64, 0).unwrap_or_else(|| Utc::now()),
-            edit_date: message.edit_date().map(|d| DateTime::from_timestamp(d as i64, 0).unwrap_or_else(|| Utc::now())),
-            forward_from_user_id: None, // TODO: Implementar forward
-            forward_from_group_id: None, // TODO: Implementar forward
-            forward_date: None, // TODO: Implementar forward
-            reply_to_message_id: message.reply_to().map(|r| r.id()),
-            media_file_id,
-            media_file_unique_id,
-            media_file_size,
-            media_mime_type,
-            media_file_name,
-            location_latitude,
-            location_longitude,
-            contact_phone_number,
-            contact_first_name,
-            contact_last_name,
+            date: message.date(),
+            edit_date: message.edit_date(),
+            forward_from_user_id,
+            forward_from_group_id,
+            forward_date: forward.date,
+            forward_from_message_id: forward.message_id,
+            forward_sender_name: forward.sender_name,
+            reply_to_message_id: message.reply_to_message_id().map(|id| id as i64),
+            media_file_id: media.file_id,
+            media_file_unique_id: media.file_unique_id,
+            media_file_size: media.file_size,
+            media_mime_type: media.mime_type,
+            media_file_name: media.file_name,
+            location_latitude: media.latitude,
+            location_longitude: media.longitude,
+            contact_phone_number: media.contact_phone_number,
+            contact_first_name: media.contact_first_name,
+            contact_last_name: media.contact_last_name,
+            collector_account_id: self.account_id.clone(),
         };
-        
-        // Salvar mensagem
-        match TelegramMessage::create(pool, new_message).await {
-            Ok(_) => {
-                info!("💾 Mensagem salva: ID {} (tipo: {})", 
+
+        // Só metadados: o registro mostra quem falou, quando e o tipo, sem o conteúdo
+        if !mode.stores_text() {
+            new_message.message_text = None;
+            new_message.media_file_name = None;
+            new_message.location_latitude = None;
+            new_message.location_longitude = None;
+            new_message.contact_phone_number = None;
+            new_message.contact_first_name = None;
+            new_message.contact_last_name = None;
+        }
+
+        let message_type = new_message.message_type.clone();
+
+        let saved = match TelegramMessage::create(pool, new_message).await {
+            Ok(saved) => {
+                info!("💾 Mensagem salva: ID {} (tipo: {})",
                     message.id(), message_type);
+                Some(saved)
             },
-            Err(e) => {
-                if e.to_string().contains("duplicate key") {
-                    info!("🔄 Mensagem duplicada ignorada: ID {}", message.id());
-                } else {
-                    warn!("❌ Erro ao salvar mensagem {}: {}", message.id(), e);
-                }
-            }
+            // ON CONFLICT DO NOTHING não retorna linha quando a mensagem já existe
+            Err(sqlx::Error::RowNotFound) => {
+                info!("🔄 Mensagem duplicada ignorada: ID {}", message.id());
+                None
+            },
+            Err(e) => return Err(format!("Erro ao salvar mensagem {}: {}", message.id(), e).into()),
+        };
+
+        if let Some(group_id) = group_id {
+            ChatSyncState::advance(pool, group_id, message.id() as i64, message.date()).await?;
         }
-        
-        Ok(())
+
+        Ok(saved)
     }
 }
+
+fn parse_forward_header(header: &tl::enums::MessageFwdHeader) -> ForwardOrigin {
+    let tl::enums::MessageFwdHeader::Header(header) = header;
+
+    let peer = header.from_id.as_ref().map(|peer| match peer {
+        tl::enums::Peer::User(user) => ForwardPeer::User(user.user_id),
+        tl::enums::Peer::Chat(chat) => ForwardPeer::Group(chat.chat_id),
+        tl::enums::Peer::Channel(channel) => ForwardPeer::Channel(channel.channel_id),
+    });
+
+    ForwardOrigin {
+        peer,
+        date: DateTime::from_timestamp(header.date as i64, 0),
+        message_id: header.channel_post.map(|id| id as i64),
+        // Remetentes que ocultam a conta no encaminhamento só expõem o nome de exibição
+        sender_name: header.from_name.clone(),
+    }
+}
+
+fn extract_media(media: &Media) -> MediaInfo {
+    match media {
+        Media::Photo(photo) => MediaInfo {
+            message_type: Some("photo"),
+            file_id: Some(photo.id().to_string()),
+            // O MTProto não expõe o file_unique_id da Bot API; repetir o ID daria um valor enganoso
+            file_unique_id: None,
+            file_size: Some(photo.size()).filter(|size| *size > 0),
+            mime_type: Some("image/jpeg".to_string()),
+            ..Default::default()
+        },
+        Media::Document(document) => {
+            let mime_type = document.mime_type().map(|s| s.to_string());
+            let message_type = match mime_type.as_deref() {
+                Some(mime) if mime.starts_with("video/") => "video",
+                Some(mime) if mime.starts_with("audio/") => "audio",
+                _ => "document",
+            };
+            MediaInfo {
+                message_type: Some(message_type),
+                file_id: Some(document.id().to_string()),
+                file_unique_id: None,
+                file_size: Some(document.size()).filter(|size| *size > 0),
+                mime_type,
+                file_name: Some(document.name().to_string()).filter(|name| !name.is_empty()),
+                ..Default::default()
+            }
+        },
+        Media::Sticker(sticker) => MediaInfo {
+            message_type: Some("sticker"),
+            file_id: Some(sticker.document.id().to_string()),
+            file_unique_id: None,
+            file_size: Some(sticker.document.size()).filter(|size| *size > 0),
+            mime_type: sticker.document.mime_type().map(|s| s.to_string()),
+            ..Default::default()
+        },
+        Media::Contact(contact) => MediaInfo {
+            message_type: Some("contact"),
+            contact_phone_number: Some(contact.phone_number().to_string()),
+            contact_first_name: Some(contact.first_name().to_string()),
+            contact_last_name: Some(contact.last_name().to_string()).filter(|name| !name.is_empty()),
+            ..Default::default()
+        },
+        Media::Geo(geo) => MediaInfo {
+            message_type: Some("location"),
+            latitude: Some(geo.latitue()),
+            longitude: Some(geo.longitude()),
+            ..Default::default()
+        },
+        Media::GeoLive(geolive) => MediaInfo {
+            message_type: Some("location"),
+            latitude: geolive.geo.as_ref().map(|geo| geo.latitue()),
+            longitude: geolive.geo.as_ref().map(|geo| geo.longitude()),
+            ..Default::default()
+        },
+        Media::Venue(venue) => MediaInfo {
+            message_type: Some("venue"),
+            latitude: venue.geo.as_ref().map(|geo| geo.latitue()),
+            longitude: venue.geo.as_ref().map(|geo| geo.longitude()),
+            ..Default::default()
+        },
+        Media::Poll(_) => MediaInfo {
+            message_type: Some("poll"),
+            ..Default::default()
+        },
+        Media::Dice(_) => MediaInfo {
+            message_type: Some("dice"),
+            ..Default::default()
+        },
+        // Pré-visualização de link: a mensagem continua sendo texto
+        _ => MediaInfo::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,53 @@
+use super::*;
+
+fn forward_header(from_id: Option<tl::enums::Peer>, from_name: Option<&str>, channel_post: Option<i32>) -> tl::enums::MessageFwdHeader {
+    tl::types::MessageFwdHeader {
+        imported: false,
+        saved_out: false,
+        from_id,
+        from_name: from_name.map(|name| name.to_string()),
+        date: 1714564800,
+        channel_post,
+        post_author: None,
+        saved_from_peer: None,
+        saved_from_msg_id: None,
+        saved_from_id: None,
+        saved_from_name: None,
+        saved_date: None,
+        psa_type: None,
+    }
+    .into()
+}
+
+#[test]
+fn channel_forward_keeps_origin_and_post_id() {
+    let header = forward_header(
+        Some(tl::types::PeerChannel { channel_id: 1234567890 }.into()),
+        None,
+        Some(4321),
+    );
+
+    let origin = parse_forward_header(&header);
+    assert_eq!(origin.peer, Some(ForwardPeer::Channel(1234567890)));
+    assert_eq!(origin.message_id, Some(4321));
+    assert_eq!(origin.date, DateTime::from_timestamp(1714564800, 0));
+    assert_eq!(origin.sender_name, None);
+}
+
+#[test]
+fn user_forward_points_to_user() {
+    let header = forward_header(Some(tl::types::PeerUser { user_id: 777 }.into()), None, None);
+
+    let origin = parse_forward_header(&header);
+    assert_eq!(origin.peer, Some(ForwardPeer::User(777)));
+    assert_eq!(origin.message_id, None);
+}
+
+#[test]
+fn hidden_sender_forward_keeps_display_name() {
+    let header = forward_header(None, Some("Anon Leaker"), None);
+
+    let origin = parse_forward_header(&header);
+    assert_eq!(origin.peer, None);
+    assert_eq!(origin.sender_name.as_deref(), Some("Anon Leaker"));
+}
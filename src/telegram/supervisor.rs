@@ -0,0 +1,255 @@
+use chrono::{DateTime, Utc};
+use grammers_client::{Client, InvocationError, ReconnectionPolicy};
+use grammers_tl_types as tl;
+use serde::Serialize;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+pub const DEFAULT_RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+pub const DEFAULT_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(300);
+
+// Conexão ociosa não entrega updates: o heartbeat confirma que ela e a autorização continuam de pé
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+// Erros que só se resolvem com um novo login; insistir apenas prolonga a coleta parada
+const AUTH_ERRORS: &[&str] = &[
+    "AUTH_KEY_UNREGISTERED",
+    "AUTH_KEY_INVALID",
+    "AUTH_KEY_PERM_EMPTY",
+    "SESSION_REVOKED",
+    "SESSION_EXPIRED",
+    "USER_DEACTIVATED",
+    "USER_DEACTIVATED_BAN",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Reconnecting { attempt: u32, retry_at: Option<DateTime<Utc>> },
+    FloodWait { until: DateTime<Utc> },
+    Unauthorized { reason: String },
+}
+
+impl ConnectionState {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, ConnectionState::Connected)
+    }
+}
+
+// Estado compartilhado entre o loop de coleta, a política de reconexão e quem expõe o health check
+#[derive(Clone)]
+pub struct ConnectionHealth {
+    state: Arc<watch::Sender<ConnectionState>>,
+}
+
+impl ConnectionHealth {
+    pub fn new() -> Self {
+        Self { state: Arc::new(watch::Sender::new(ConnectionState::Connecting)) }
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state.borrow().clone()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.state.borrow().is_healthy()
+    }
+
+    pub fn set(&self, state: ConnectionState) {
+        self.state.send_if_modified(|current| {
+            if *current == state {
+                return false;
+            }
+            *current = state;
+            true
+        });
+    }
+
+    // Resolve assim que qualquer parte do cliente detectar a sessão revogada
+    pub async fn unauthorized(&self) -> String {
+        let mut receiver = self.state.subscribe();
+        loop {
+            if let ConnectionState::Unauthorized { reason } = &*receiver.borrow_and_update() {
+                return reason.clone();
+            }
+            if receiver.changed().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+impl Default for ConnectionHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    FloodWait(Duration),
+    Unauthorized(String),
+    Transient,
+}
+
+pub fn classify(error: &InvocationError) -> Failure {
+    if let Some(delay) = super::flood_wait(error) {
+        return Failure::FloodWait(delay);
+    }
+    match error {
+        InvocationError::Rpc(rpc) if rpc.code == 401 || AUTH_ERRORS.iter().any(|name| rpc.is(name)) => {
+            Failure::Unauthorized(rpc.name.clone())
+        },
+        _ => Failure::Transient,
+    }
+}
+
+// Backoff exponencial com "equal jitter": metade fixa, metade sorteada, para as contas não reconectarem em sincronia
+pub fn backoff_delay(attempt: u32, base: Duration, max: Duration, jitter: f64) -> Duration {
+    let exponential = base.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1))).min(max);
+    let half = exponential / 2;
+    half + half.mul_f64(jitter.clamp(0.0, 1.0))
+}
+
+#[derive(Debug)]
+pub struct SessionRevoked {
+    pub reason: String,
+}
+
+impl std::fmt::Display for SessionRevoked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sessão do Telegram revogada ou inválida ({}): apague o arquivo de sessão e faça login novamente", self.reason)
+    }
+}
+
+impl std::error::Error for SessionRevoked {}
+
+// Usada pelo próprio grammers ao perder a conexão: reconecta no mesmo Client, sem invalidar os clones
+// já entregues ao notificador e aos downloads
+pub struct JitteredReconnect {
+    base: Duration,
+    max: Duration,
+    health: ConnectionHealth,
+}
+
+impl JitteredReconnect {
+    pub fn new(base: Duration, max: Duration, health: ConnectionHealth) -> Self {
+        Self { base, max, health }
+    }
+
+    // O grammers exige uma referência 'static; uma por conexão aberta
+    pub fn leak(self) -> &'static dyn ReconnectionPolicy {
+        Box::leak(Box::new(self))
+    }
+}
+
+impl ReconnectionPolicy for JitteredReconnect {
+    fn should_retry(&self, attempts: usize) -> ControlFlow<(), Duration> {
+        // Com zero tentativas o grammers só pergunta se deve reconectar
+        if attempts == 0 {
+            warn!("🔌 Conexão com o Telegram perdida, reconectando...");
+            self.health.set(ConnectionState::Reconnecting { attempt: 0, retry_at: None });
+            return ControlFlow::Continue(Duration::ZERO);
+        }
+
+        let attempt = attempts.min(u32::MAX as usize) as u32;
+        let delay = backoff_delay(attempt, self.base, self.max, rand::random());
+        warn!("🔁 Tentativa {} de reconexão falhou; nova tentativa em {:?}", attempt, delay);
+        self.health.set(ConnectionState::Reconnecting {
+            attempt,
+            retry_at: chrono::Duration::from_std(delay).ok().map(|delay| Utc::now() + delay),
+        });
+        ControlFlow::Continue(delay)
+    }
+}
+
+pub fn spawn_heartbeat(client: Client, health: ConnectionHealth) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            match client.invoke(&tl::functions::updates::GetState {}).await {
+                Ok(_) => {
+                    // Um FLOOD_WAIT ainda em curso continua valendo mesmo com a conexão de pé
+                    let flooded = matches!(health.state(), ConnectionState::FloodWait { until } if until > Utc::now());
+                    if !flooded {
+                        health.set(ConnectionState::Connected);
+                    }
+                },
+                Err(e) => match classify(&e) {
+                    Failure::Unauthorized(reason) => {
+                        health.set(ConnectionState::Unauthorized { reason });
+                        return;
+                    },
+                    Failure::FloodWait(delay) => {
+                        if let Ok(delay) = chrono::Duration::from_std(delay) {
+                            health.set(ConnectionState::FloodWait { until: Utc::now() + delay });
+                        }
+                    },
+                    Failure::Transient => warn!("💓 Heartbeat com o Telegram falhou: {}", e),
+                },
+            }
+        }
+    })
+}
+
+pub struct Supervisor {
+    base: Duration,
+    max: Duration,
+    health: ConnectionHealth,
+    failures: u32,
+}
+
+impl Supervisor {
+    pub fn new(base: Duration, max: Duration, health: ConnectionHealth) -> Self {
+        Self { base, max, health, failures: 0 }
+    }
+
+    pub fn connected(&mut self) {
+        if self.failures > 0 {
+            info!("✅ Recebimento de updates restabelecido após {} falha(s)", self.failures);
+        }
+        self.failures = 0;
+        self.health.set(ConnectionState::Connected);
+    }
+
+    // Espera o necessário antes de pedir o próximo update; erro só quando a sessão não serve mais
+    pub async fn recover(&mut self, error: &InvocationError) -> Result<(), SessionRevoked> {
+        match classify(error) {
+            Failure::Unauthorized(reason) => {
+                self.health.set(ConnectionState::Unauthorized { reason: reason.clone() });
+                Err(SessionRevoked { reason })
+            },
+            Failure::FloodWait(delay) => {
+                warn!("⏳ FLOOD_WAIT ao receber updates, aguardando {}s", delay.as_secs());
+                if let Ok(wait) = chrono::Duration::from_std(delay) {
+                    self.health.set(ConnectionState::FloodWait { until: Utc::now() + wait });
+                }
+                tokio::time::sleep(delay).await;
+                Ok(())
+            },
+            Failure::Transient => {
+                self.failures = self.failures.saturating_add(1);
+                let delay = backoff_delay(self.failures, self.base, self.max, rand::random());
+                warn!("❌ Erro ao receber update ({}); nova tentativa em {:?}", error, delay);
+                self.health.set(ConnectionState::Reconnecting {
+                    attempt: self.failures,
+                    retry_at: chrono::Duration::from_std(delay).ok().map(|delay| Utc::now() + delay),
+                });
+                tokio::time::sleep(delay).await;
+                Ok(())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
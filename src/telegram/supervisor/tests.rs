@@ -0,0 +1,71 @@
+use super::*;
+use grammers_tl_types::types::RpcError as RawRpcError;
+
+fn rpc_error(code: i32, message: &str) -> InvocationError {
+    InvocationError::Rpc(RawRpcError { error_code: code, error_message: message.to_string() }.into())
+}
+
+#[test]
+fn backoff_grows_exponentially_within_jitter_bounds() {
+    let base = Duration::from_secs(1);
+    let max = Duration::from_secs(60);
+
+    assert_eq!(backoff_delay(1, base, max, 0.0), Duration::from_millis(500));
+    assert_eq!(backoff_delay(1, base, max, 1.0), Duration::from_secs(1));
+    assert_eq!(backoff_delay(4, base, max, 0.0), Duration::from_secs(4));
+    assert_eq!(backoff_delay(4, base, max, 1.0), Duration::from_secs(8));
+
+    // O teto vale para o valor sem jitter; tentativas muito altas não estouram
+    assert_eq!(backoff_delay(30, base, max, 1.0), max);
+    assert_eq!(backoff_delay(u32::MAX, base, max, 0.0), Duration::from_secs(30));
+
+    for _ in 0..100 {
+        let delay = backoff_delay(3, base, max, rand::random());
+        assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(4));
+    }
+}
+
+#[test]
+fn classifies_flood_wait_and_revoked_sessions() {
+    assert_eq!(classify(&rpc_error(420, "FLOOD_WAIT_31")), Failure::FloodWait(Duration::from_secs(31)));
+    assert_eq!(classify(&rpc_error(401, "AUTH_KEY_UNREGISTERED")), Failure::Unauthorized("AUTH_KEY_UNREGISTERED".to_string()));
+    assert_eq!(classify(&rpc_error(401, "SESSION_REVOKED")), Failure::Unauthorized("SESSION_REVOKED".to_string()));
+    assert_eq!(classify(&rpc_error(500, "RPC_CALL_FAIL")), Failure::Transient);
+    assert_eq!(classify(&InvocationError::Dropped), Failure::Transient);
+}
+
+#[test]
+fn reconnect_policy_never_gives_up_and_reports_attempts() {
+    let health = ConnectionHealth::new();
+    let policy = JitteredReconnect::new(Duration::from_millis(100), Duration::from_secs(1), health.clone());
+
+    assert_eq!(policy.should_retry(0), ControlFlow::Continue(Duration::ZERO));
+    assert_eq!(health.state(), ConnectionState::Reconnecting { attempt: 0, retry_at: None });
+
+    match policy.should_retry(50) {
+        ControlFlow::Continue(delay) => assert!(delay <= Duration::from_secs(1)),
+        ControlFlow::Break(()) => panic!("a política não deve desistir"),
+    }
+    assert!(matches!(health.state(), ConnectionState::Reconnecting { attempt: 50, retry_at: Some(_) }));
+    assert!(!health.is_healthy());
+}
+
+#[tokio::test]
+async fn supervisor_stops_on_revoked_session_and_notifies_waiters() {
+    let health = ConnectionHealth::new();
+    let mut supervisor = Supervisor::new(Duration::from_millis(1), Duration::from_millis(2), health.clone());
+    let waiter = tokio::spawn({
+        let health = health.clone();
+        async move { health.unauthorized().await }
+    });
+
+    supervisor.recover(&InvocationError::Dropped).await.unwrap();
+    assert!(matches!(health.state(), ConnectionState::Reconnecting { attempt: 1, .. }));
+
+    supervisor.connected();
+    assert!(health.is_healthy());
+
+    let revoked = supervisor.recover(&rpc_error(401, "AUTH_KEY_UNREGISTERED")).await.unwrap_err();
+    assert!(revoked.to_string().contains("AUTH_KEY_UNREGISTERED"));
+    assert_eq!(waiter.await.unwrap(), "AUTH_KEY_UNREGISTERED");
+}
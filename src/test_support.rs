@@ -0,0 +1,11 @@
+use std::net::SocketAddr;
+
+// Sobe o router numa porta livre de 127.0.0.1 e devolve o endereço
+pub async fn serve(router: axum::Router) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    address
+}
@@ -0,0 +1,222 @@
+use crate::db::{Database, WatchlistRule};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::warn;
+use uuid::Uuid;
+
+// Regras novas cadastradas pelos analistas passam a valer sem reiniciar o coletor
+const RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchType {
+    Literal,
+    CaseInsensitive,
+    Regex,
+    Fuzzy,
+}
+
+impl MatchType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "literal" => Some(MatchType::Literal),
+            "case_insensitive" => Some(MatchType::CaseInsensitive),
+            "regex" => Some(MatchType::Regex),
+            "fuzzy" => Some(MatchType::Fuzzy),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchType::Literal => "literal",
+            MatchType::CaseInsensitive => "case_insensitive",
+            MatchType::Regex => "regex",
+            MatchType::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+// Intervalo em caracteres (não bytes) dentro do texto da mensagem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchlistMatch {
+    pub rule_id: Uuid,
+    pub start: i32,
+    pub end: i32,
+    pub matched_text: String,
+}
+
+enum Matcher {
+    Pattern(Regex),
+    Fuzzy { words: Vec<String>, max_distance: usize },
+}
+
+struct CompiledRule {
+    id: Uuid,
+    matcher: Matcher,
+}
+
+#[derive(Default)]
+pub struct Watchlist {
+    rules: Vec<CompiledRule>,
+}
+
+impl Watchlist {
+    pub fn compile(rules: &[WatchlistRule]) -> Self {
+        let rules = rules.iter().filter_map(|rule| {
+            let Some(match_type) = MatchType::parse(&rule.match_type) else {
+                warn!("⚠️ Regra {} ignorada: tipo de correspondência desconhecido '{}'", rule.name, rule.match_type);
+                return None;
+            };
+
+            let matcher = match match_type {
+                MatchType::Literal => Regex::new(&regex::escape(&rule.pattern)).map(Matcher::Pattern),
+                MatchType::CaseInsensitive => Regex::new(&format!("(?i){}", regex::escape(&rule.pattern))).map(Matcher::Pattern),
+                MatchType::Regex => Regex::new(&rule.pattern).map(Matcher::Pattern),
+                MatchType::Fuzzy => {
+                    let words: Vec<String> = words_of(&rule.pattern).into_iter().map(|(_, _, word)| word).collect();
+                    let length: usize = words.iter().map(|word| word.chars().count()).sum();
+                    let max_distance = rule.max_distance
+                        .and_then(|distance| usize::try_from(distance).ok())
+                        .unwrap_or((length / 5).max(1));
+                    Ok(Matcher::Fuzzy { words, max_distance })
+                },
+            };
+
+            match matcher {
+                Ok(matcher) => Some(CompiledRule { id: rule.id, matcher }),
+                Err(e) => {
+                    warn!("⚠️ Regra {} ignorada: regex inválida: {}", rule.name, e);
+                    None
+                },
+            }
+        }).collect();
+
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn find_matches(&self, text: &str) -> Vec<WatchlistMatch> {
+        let mut matches = Vec::new();
+
+        for rule in &self.rules {
+            let spans: Vec<(usize, usize)> = match &rule.matcher {
+                Matcher::Pattern(regex) => regex.find_iter(text)
+                    .filter(|found| !found.is_empty())
+                    .map(|found| (found.start(), found.end()))
+                    .collect(),
+                Matcher::Fuzzy { words, max_distance } => fuzzy_spans(text, words, *max_distance),
+            };
+
+            matches.extend(spans.into_iter().map(|(start, end)| WatchlistMatch {
+                rule_id: rule.id,
+                start: text[..start].chars().count() as i32,
+                end: text[..end].chars().count() as i32,
+                matched_text: text[start..end].to_string(),
+            }));
+        }
+
+        matches
+    }
+}
+
+type CachedWatchlist = Option<(Instant, Arc<Watchlist>)>;
+
+// Mantém as regras compiladas em memória e recarrega do banco periodicamente
+#[derive(Clone)]
+pub struct WatchlistCache {
+    database: Database,
+    cached: Arc<RwLock<CachedWatchlist>>,
+}
+
+impl WatchlistCache {
+    pub fn new(database: Database) -> Self {
+        Self {
+            database,
+            cached: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub async fn current(&self) -> Result<Arc<Watchlist>, sqlx::Error> {
+        if let Some((loaded_at, watchlist)) = self.cached.read().await.as_ref() {
+            if loaded_at.elapsed() < RELOAD_INTERVAL {
+                return Ok(watchlist.clone());
+            }
+        }
+
+        let rules = WatchlistRule::find_enabled(self.database.get_pool()).await?;
+        let watchlist = Arc::new(Watchlist::compile(&rules));
+        *self.cached.write().await = Some((Instant::now(), watchlist.clone()));
+
+        Ok(watchlist)
+    }
+}
+
+// Compara janelas de palavras consecutivas com a frase da regra, tolerando erros de digitação
+fn fuzzy_spans(text: &str, words: &[String], max_distance: usize) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    if words.is_empty() {
+        return spans;
+    }
+
+    let pattern = words.join(" ");
+    let tokens = words_of(text);
+    let mut index = 0;
+
+    while index + words.len() <= tokens.len() {
+        let window = &tokens[index..index + words.len()];
+        let candidate = window.iter().map(|(_, _, word)| word.as_str()).collect::<Vec<_>>().join(" ");
+
+        if levenshtein(&candidate, &pattern) <= max_distance {
+            spans.push((window[0].0, window[window.len() - 1].1));
+            index += words.len();
+        } else {
+            index += 1;
+        }
+    }
+
+    spans
+}
+
+fn words_of(text: &str) -> Vec<(usize, usize, String)> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (position, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(position),
+            (Some(word_start), false) => {
+                words.push((word_start, position, text[word_start..position].to_lowercase()));
+                start = None;
+            },
+            _ => {},
+        }
+    }
+
+    words
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests;
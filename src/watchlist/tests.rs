@@ -0,0 +1,63 @@
+use super::*;
+use chrono::Utc;
+
+fn rule(pattern: &str, match_type: MatchType) -> WatchlistRule {
+    WatchlistRule {
+        id: Uuid::new_v4(),
+        name: pattern.to_string(),
+        pattern: pattern.to_string(),
+        match_type: match_type.as_str().to_string(),
+        max_distance: None,
+        enabled: true,
+        created_at: Utc::now(),
+    }
+}
+
+fn matched(watchlist: &Watchlist, text: &str) -> Vec<String> {
+    watchlist.find_matches(text).into_iter().map(|found| found.matched_text).collect()
+}
+
+#[test]
+fn literal_rules_are_case_sensitive() {
+    let watchlist = Watchlist::compile(&[rule("ACME", MatchType::Literal)]);
+    assert_eq!(matched(&watchlist, "vazamento da ACME e da acme"), vec!["ACME"]);
+}
+
+#[test]
+fn case_insensitive_rules_escape_the_pattern() {
+    let watchlist = Watchlist::compile(&[rule("acme.com", MatchType::CaseInsensitive)]);
+    assert_eq!(matched(&watchlist, "login em ACME.COM, não em acmexcom"), vec!["ACME.COM"]);
+}
+
+#[test]
+fn regex_rules_match_bins() {
+    let watchlist = Watchlist::compile(&[rule(r"\b45173[0-9]{1}\b", MatchType::Regex)]);
+    assert_eq!(matched(&watchlist, "bins 451731 e 451732 fresh, 999999 não"), vec!["451731", "451732"]);
+}
+
+#[test]
+fn invalid_regex_rules_are_skipped() {
+    let watchlist = Watchlist::compile(&[rule("(", MatchType::Regex), rule("acme", MatchType::Literal)]);
+    assert_eq!(matched(&watchlist, "acme"), vec!["acme"]);
+}
+
+#[test]
+fn fuzzy_rules_tolerate_typos_in_names() {
+    let watchlist = Watchlist::compile(&[rule("Joana Silveira", MatchType::Fuzzy)]);
+    assert_eq!(matched(&watchlist, "dados da CEO joanna silveira vazados"), vec!["joanna silveira"]);
+    assert!(matched(&watchlist, "dados da CEO Maria Souza").is_empty());
+}
+
+#[test]
+fn spans_are_counted_in_characters() {
+    let watchlist = Watchlist::compile(&[rule("acme", MatchType::Literal)]);
+    let found = watchlist.find_matches("ação acme");
+    assert_eq!((found[0].start, found[0].end), (5, 9));
+}
+
+#[test]
+fn levenshtein_counts_edits() {
+    assert_eq!(levenshtein("kitten", "sitting"), 3);
+    assert_eq!(levenshtein("", "abc"), 3);
+    assert_eq!(levenshtein("acme", "acme"), 0);
+}